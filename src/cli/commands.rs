@@ -40,6 +40,10 @@ pub enum Commands {
         /// Dry run - don't actually send notification
         #[arg(long)]
         dry_run: bool,
+
+        /// Wait for remote Allow/Deny on PreToolUse even if approval is disabled in config
+        #[arg(long)]
+        remote_approval: bool,
     },
 
     /// Initialize configuration
@@ -83,6 +87,21 @@ pub enum Commands {
         topic: Option<String>,
     },
 
+    /// Print daemon task events as they happen
+    Tail {
+        /// Only tasks for this hook
+        #[arg(long)]
+        hook: Option<String>,
+
+        /// Only tasks whose project path contains this
+        #[arg(long)]
+        project_path: Option<String>,
+
+        /// Print one JSON object per event
+        #[arg(long)]
+        json: bool,
+    },
+
     /// List available templates
     Templates {
         /// Show template content
@@ -140,9 +159,56 @@ pub enum DaemonAction {
     /// Stop the daemon
     Stop,
 
-    /// Check daemon status
-    Status,
+    /// Check daemon status and delivery statistics
+    Status {
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Report every running daemon, global and per-project
+        #[arg(long)]
+        all: bool,
+    },
 
     /// Reload daemon configuration
     Reload,
+
+    /// Inspect notifications that could not be delivered
+    Dlq {
+        #[command(subcommand)]
+        action: DlqAction,
+    },
+}
+
+/// Dead-letter queue actions
+#[derive(Subcommand)]
+pub enum DlqAction {
+    /// List dead letters
+    List,
+
+    /// Show a dead letter with its hook data and failed attempts
+    Show {
+        /// Dead letter id
+        id: String,
+    },
+
+    /// Queue dead letters for delivery again
+    Retry {
+        /// Dead letter id
+        #[arg(required_unless_present = "all")]
+        id: Option<String>,
+        /// Retry every dead letter
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+    },
+
+    /// Delete dead letters
+    Purge {
+        /// Dead letter id
+        #[arg(required_unless_present = "all")]
+        id: Option<String>,
+        /// Delete every dead letter
+        #[arg(long, conflicts_with = "id")]
+        all: bool,
+    },
 }
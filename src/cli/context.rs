@@ -4,9 +4,16 @@
 //! configuration management and reduces coupling in CLI handlers.
 
 use std::path::PathBuf;
-use std::sync::Arc;
-use anyhow::Result;
+use std::sync::{Arc, OnceLock};
+use anyhow::{Context, Result};
+use tracing_subscriber::filter::{Directive, EnvFilter, LevelFilter};
 use crate::config::ConfigManager;
+use crate::daemon::ipc::{daemon_project, daemon_socket_path};
+
+/// Replaces the log filter installed by [`CliContext::init_logging`]
+type FilterReloader = Box<dyn Fn(EnvFilter) -> Result<()> + Send + Sync>;
+
+static LOG_FILTER: OnceLock<FilterReloader> = OnceLock::new();
 
 /// CLI execution context containing shared dependencies and configuration
 #[derive(Clone)]
@@ -48,6 +55,16 @@ impl CliContext {
         None
     }
 
+    /// Socket of the daemon this invocation talks to, per `daemon.scope`
+    pub fn daemon_socket_path(&self) -> Result<PathBuf> {
+        daemon_socket_path(&self.config_manager.config().daemon, self.project_path.as_ref())
+    }
+
+    /// Project served by that daemon; `None` for the global daemon
+    pub fn daemon_project(&self) -> Option<&PathBuf> {
+        daemon_project(&self.config_manager.config().daemon, self.project_path.as_ref())
+    }
+
     /// Initialize logging subsystem based on verbosity and configuration
     pub fn init_logging(&self) -> Result<()> {
         let log_level = if self.verbose { 
@@ -56,14 +73,21 @@ impl CliContext {
             &self.config_manager.config().daemon.log_level
         };
         
-        tracing_subscriber::fmt()
+        // Log to stderr: hook stdout is reserved for Claude Code decision output
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
             .with_env_filter(
-                tracing_subscriber::EnvFilter::from_default_env()
+                EnvFilter::from_default_env()
                     .add_directive(log_level.parse().unwrap_or_else(|_| {
                         tracing::Level::INFO.into()
                     })),
             )
-            .init();
+            .with_filter_reloading();
+        let handle = subscriber.reload_handle();
+        let _ = LOG_FILTER.set(Box::new(move |filter| {
+            handle.reload(filter).context("Failed to replace log filter")
+        }));
+        subscriber.init();
 
         if self.verbose {
             tracing::debug!("Verbose logging enabled");
@@ -74,6 +98,31 @@ impl CliContext {
         Ok(())
     }
 
+    /// Parse a `daemon.log_level` value such as `debug` or `claude_ntfy=trace`
+    ///
+    /// A bare word must be a level; anything else would silently be taken
+    /// as the name of a log target.
+    pub fn parse_log_level(level: &str) -> Result<Directive> {
+        let directive = if level.contains('=') {
+            level.parse().map_err(anyhow::Error::from)
+        } else {
+            level
+                .parse::<LevelFilter>()
+                .map(Directive::from)
+                .map_err(anyhow::Error::from)
+        };
+        directive.with_context(|| format!("Invalid daemon.log_level '{level}'"))
+    }
+
+    /// Switch the running process to a new log level
+    ///
+    /// Does nothing when logging has not been initialized.
+    pub fn set_log_level(level: Directive) -> Result<()> {
+        match LOG_FILTER.get() {
+            Some(reload) => reload(EnvFilter::from_default_env().add_directive(level)),
+            None => Ok(()),
+        }
+    }
 }
 
 
@@ -115,6 +164,13 @@ mod tests {
         assert_eq!(context.project_path, Some(temp_dir.path().to_path_buf()));
     }
 
+    #[test]
+    fn test_parse_log_level() {
+        assert!(CliContext::parse_log_level("debug").is_ok());
+        assert!(CliContext::parse_log_level("claude_ntfy=trace").is_ok());
+        assert!(CliContext::parse_log_level("verbose").is_err());
+    }
+
     #[test]
     fn test_global_context() {
         let context = CliContext::new(None, false).unwrap();
@@ -32,9 +32,9 @@ impl CommandHandler {
         let builder = HandlerBuilder::new(&self.context);
         
         match command {
-            Commands::Hook { hook_name, no_daemon, dry_run } => {
+            Commands::Hook { hook_name, no_daemon, dry_run, remote_approval } => {
                 let hook_handler = builder.create_with_context::<hook::HookHandler>();
-                hook_handler.handle_hook(hook_name, no_daemon, dry_run).await
+                hook_handler.handle_hook(hook_name, no_daemon, dry_run, remote_approval).await
             }
             Commands::Init { global, force } => {
                 let config_handler = builder.create_with_context::<config::ConfigHandler>();
@@ -48,6 +48,10 @@ impl CommandHandler {
                 let daemon_handler = builder.create_with_context::<daemon::DaemonHandler>();
                 daemon_handler.handle_daemon(action).await
             }
+            Commands::Tail { hook, project_path, json } => {
+                let daemon_handler = builder.create_with_context::<daemon::DaemonHandler>();
+                daemon_handler.handle_tail(hook, project_path, json).await
+            }
             Commands::Test { message, title, priority, topic } => {
                 let test_handler = builder.create_with_context::<test::TestHandler>();
                 test_handler.handle_test(message, title, priority, topic).await
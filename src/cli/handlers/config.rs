@@ -4,6 +4,8 @@
 //! initialization, setting values, and hook configuration.
 
 use super::super::{CliContext, ConfigAction};
+use crate::hooks::filter::FilterRule;
+use crate::shared::config::DaemonScope;
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
@@ -78,6 +80,15 @@ impl<'a> ConfigHandler<'a> {
                         config_manager.config_mut().ntfy.auth_token = Some(value.clone())
                     }
                     "daemon.enabled" => config_manager.config_mut().daemon.enabled = value.parse()?,
+                    "daemon.autostart" => config_manager.config_mut().daemon.autostart = value.parse()?,
+                    "daemon.scope" => {
+                        config_manager.config_mut().daemon.scope = match value.as_str() {
+                            "global" => DaemonScope::Global,
+                            "project" => DaemonScope::Project,
+                            _ => return Err(anyhow::anyhow!("Daemon scope must be 'global' or 'project'")),
+                        }
+                    }
+                    "daemon.watch_config" => config_manager.config_mut().daemon.watch_config = value.parse()?,
                     "daemon.log_path" => {
                         config_manager.config_mut().daemon.log_path = if value.is_empty() {
                             None
@@ -85,12 +96,45 @@ impl<'a> ConfigHandler<'a> {
                             Some(value.clone())
                         }
                     }
+                    "daemon.listen_address" => {
+                        config_manager.config_mut().daemon.listen_address = if value.is_empty() {
+                            None
+                        } else {
+                            Some(value.clone())
+                        }
+                    }
+                    "daemon.remote_address" => {
+                        config_manager.config_mut().daemon.remote_address = if value.is_empty() {
+                            None
+                        } else {
+                            Some(value.clone())
+                        }
+                    }
+                    "daemon.auth_token" => {
+                        config_manager.config_mut().daemon.auth_token = if value.is_empty() {
+                            None
+                        } else {
+                            Some(value.clone())
+                        }
+                    }
                     "hooks.never_filter_decision_hooks" => {
                         config_manager.config_mut().hooks.never_filter_decision_hooks = value.parse()?
                     }
+                    "approval.enabled" => config_manager.config_mut().approval.enabled = value.parse()?,
+                    "approval.reply_topic" => {
+                        config_manager.config_mut().approval.reply_topic = if value.is_empty() {
+                            None
+                        } else {
+                            Some(value.clone())
+                        }
+                    }
+                    "approval.timeout_secs" => {
+                        config_manager.config_mut().approval.timeout_secs =
+                            value.parse().context("Timeout must be a number of seconds")?
+                    }
                     "hooks.decision_hook_priority" => {
                         let priority: u8 = value.parse().context("Priority must be a number 1-5")?;
-                        if priority < 1 || priority > 5 {
+                        if !(1..=5).contains(&priority) {
                             return Err(anyhow::anyhow!("Priority must be between 1 and 5"));
                         }
                         config_manager.config_mut().hooks.decision_hook_priority = priority;
@@ -105,16 +149,36 @@ impl<'a> ConfigHandler<'a> {
                     "ntfy.server_url" => config_manager.config().ntfy.server_url.clone(),
                     "ntfy.default_topic" => config_manager.config().ntfy.default_topic.clone(),
                     "daemon.enabled" => config_manager.config().daemon.enabled.to_string(),
+                    "daemon.autostart" => config_manager.config().daemon.autostart.to_string(),
+                    "daemon.scope" => match config_manager.config().daemon.scope {
+                        DaemonScope::Global => "global".to_string(),
+                        DaemonScope::Project => "project".to_string(),
+                    },
+                    "daemon.watch_config" => config_manager.config().daemon.watch_config.to_string(),
                     "daemon.log_path" => config_manager.config().daemon.log_path
                         .as_ref()
                         .cloned()
                         .unwrap_or_else(|| "None".to_string()),
+                    "daemon.listen_address" => config_manager.config().daemon.listen_address
+                        .as_ref()
+                        .cloned()
+                        .unwrap_or_else(|| "None".to_string()),
+                    "daemon.remote_address" => config_manager.config().daemon.remote_address
+                        .as_ref()
+                        .cloned()
+                        .unwrap_or_else(|| "None".to_string()),
                     "hooks.never_filter_decision_hooks" => {
                         config_manager.config().hooks.never_filter_decision_hooks.to_string()
                     }
                     "hooks.decision_hook_priority" => {
                         config_manager.config().hooks.decision_hook_priority.to_string()
                     }
+                    "approval.enabled" => config_manager.config().approval.enabled.to_string(),
+                    "approval.reply_topic" => config_manager.config().approval.reply_topic
+                        .as_ref()
+                        .cloned()
+                        .unwrap_or_else(|| "None".to_string()),
+                    "approval.timeout_secs" => config_manager.config().approval.timeout_secs.to_string(),
                     _ => return Err(anyhow::anyhow!("Unknown configuration key: {}", key)),
                 };
                 println!("{value}");
@@ -140,6 +204,8 @@ impl<'a> ConfigHandler<'a> {
                         .insert(name.clone(), priority);
                 }
                 if let Some(filter) = filter {
+                    FilterRule::parse(&filter)
+                        .with_context(|| format!("Invalid filter rule: {filter}"))?;
                     config_manager
                         .config_mut()
                         .hooks
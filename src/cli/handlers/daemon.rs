@@ -1,19 +1,37 @@
 //! Daemon management handler
 //!
 //! This module handles all daemon-related commands including start, stop,
-//! status checks, and reload operations.
+//! status checks, reload operations and the dead-letter queue.
 
-use super::super::{CliContext, DaemonAction};
+use super::super::{CliContext, DaemonAction, DlqAction};
+use crate::config::ConfigManager;
+use crate::daemon::queue::TaskQueue;
+use crate::daemon::server::NotificationDaemon;
+use crate::daemon::shared::{DaemonEvent, DeliveryStats, EventFilter, EventKind};
+use crate::daemon::spool::SpooledTask;
+use crate::daemon::watcher::ConfigWatcher;
+use crate::daemon::instances::{DaemonInstance, InstanceRegistry};
 use crate::daemon::{
-    DaemonResponse, NotificationTask,
-    create_socket_path, is_process_running
+    DaemonResponse,
+    is_process_running
+};
+use crate::daemon::ipc::{remove_stale_socket, write_pid_file};
+use crate::shared::clients::format::truncate;
+use crate::shared::clients::traits::RetryConfig;
+use crate::shared::clients::BackendRegistry;
+use crate::shared::ipc::convenience::{
+    get_daemon_status, list_dead_letters, purge_dead_letters, reload_daemon, retry_dead_letters, shutdown_daemon,
+    subscribe_events,
 };
-use crate::shared::ipc::convenience::{get_daemon_status, shutdown_daemon, reload_daemon};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
+/// How often `daemon.watch_config` checks configuration files for changes
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
 /// Handler for daemon operations
 pub struct DaemonHandler<'a> {
     context: &'a CliContext,
@@ -34,12 +52,18 @@ impl<'a> DaemonHandler<'a> {
             DaemonAction::Stop => {
                 self.handle_daemon_stop().await
             }
-            DaemonAction::Status => {
-                self.handle_daemon_status().await
+            DaemonAction::Status { json, all } if all => {
+                self.handle_all_daemons_status(json).await
+            }
+            DaemonAction::Status { json, .. } => {
+                self.handle_daemon_status(json).await
             }
             DaemonAction::Reload => {
                 self.handle_daemon_reload().await
             }
+            DaemonAction::Dlq { action } => {
+                self.handle_dlq(action).await
+            }
         }
     }
 
@@ -136,34 +160,116 @@ impl<'a> DaemonHandler<'a> {
     }
 
     /// Handle daemon status command
-    pub async fn handle_daemon_status(&self) -> Result<()> {
-        let (pid_file, _) = self.get_daemon_paths()?;
+    pub async fn handle_daemon_status(&self, json: bool) -> Result<()> {
+        let (pid_file, socket_path) = self.get_daemon_paths()?;
         
         match self.check_daemon_process(&pid_file)? {
-            Some(pid_num) => {
-                // Try to get detailed status via IPC
-                let (_, socket_path) = self.get_daemon_paths()?;
-                match get_daemon_status(&socket_path).await {
-                    Ok(DaemonResponse::Status { queue_size, is_running: _, uptime_secs }) => {
-                        println!("Daemon is running (PID: {})", pid_num);
-                        println!("  Queue size: {}", queue_size);
-                        println!("  Uptime: {} seconds", uptime_secs);
-                        println!("  IPC Status: Connected");
-                    }
-                    Ok(_) => {
-                        println!("Daemon is running (PID: {}) - Unexpected status response", pid_num);
-                    }
-                    Err(e) => {
-                        println!("Daemon is running (PID: {}) - IPC communication failed: {}", pid_num, e);
-                    }
-                }
+            Some(pid_num) if json => {
+                let output = Self::status_json(pid_num, &socket_path).await?;
+                println!("{}", serde_json::to_string_pretty(&output)?);
             }
+            Some(pid_num) => Self::print_status(pid_num, &socket_path).await,
+            None if json => println!("{}", serde_json::json!({ "is_running": false })),
             None => println!("Daemon is not running"),
         }
         
         Ok(())
     }
 
+    /// Handle `daemon status --all`, reporting every running daemon
+    pub async fn handle_all_daemons_status(&self, json: bool) -> Result<()> {
+        let instances = InstanceRegistry::global()?.running()?;
+
+        if json {
+            let mut statuses = Vec::new();
+            for instance in &instances {
+                let mut output = Self::status_json(instance.pid, &instance.socket_path).await?;
+                output["socket_path"] = serde_json::json!(instance.socket_path);
+                output["project_path"] = serde_json::json!(instance.project_path);
+                statuses.push(output);
+            }
+            println!("{}", serde_json::to_string_pretty(&statuses)?);
+            return Ok(());
+        }
+
+        if instances.is_empty() {
+            println!("No daemons are running");
+        }
+        for (index, instance) in instances.iter().enumerate() {
+            if index > 0 {
+                println!();
+            }
+            match &instance.project_path {
+                Some(project) => println!("Project daemon for {}", project.display()),
+                None => println!("Global daemon"),
+            }
+            Self::print_status(instance.pid, &instance.socket_path).await;
+        }
+
+        Ok(())
+    }
+
+    /// Detailed status of a running daemon as JSON, including its PID
+    async fn status_json(pid: u32, socket_path: &Path) -> Result<serde_json::Value> {
+        let mut output = match get_daemon_status(socket_path).await {
+            Ok(DaemonResponse::Status(status)) => serde_json::to_value(&status)?,
+            Ok(_) => serde_json::json!({ "is_running": true, "error": "Unexpected status response" }),
+            Err(e) => serde_json::json!({ "is_running": true, "error": format!("IPC communication failed: {e}") }),
+        };
+        output["pid"] = pid.into();
+        Ok(output)
+    }
+
+    /// Print the detailed status of a running daemon
+    async fn print_status(pid: u32, socket_path: &Path) {
+        match get_daemon_status(socket_path).await {
+            Ok(DaemonResponse::Status(status)) => {
+                println!("Daemon is running (PID: {})", pid);
+                println!("  Queue size: {}", status.queue_size);
+                println!("  Waiting for retry: {}", status.retry_queue_size);
+                println!("  Workers: {}", status.workers);
+                println!("  Uptime: {} seconds", status.uptime_secs);
+                println!("  IPC Status: Connected");
+                Self::print_stats("Projects", &status.projects);
+                Self::print_stats("Hooks", &status.hooks);
+                Self::print_stats("Backends", &status.backends);
+            }
+            Ok(_) => {
+                println!("Daemon is running (PID: {}) - Unexpected status response", pid);
+            }
+            Err(e) => {
+                println!("Daemon is running (PID: {}) - IPC communication failed: {}", pid, e);
+            }
+        }
+    }
+
+    /// Print one breakdown of the delivery statistics as a table
+    fn print_stats(title: &str, entries: &[DeliveryStats]) {
+        if entries.is_empty() {
+            return;
+        }
+
+        let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0).max(title.len());
+        println!();
+        println!(
+            "{title:<width$}  {:>6}  {:>6}  {:>7}  {:>7}  {:>7}  {:>7}  {:>7}",
+            "sent", "failed", "retries", "p50", "p90", "p99", "max"
+        );
+        for entry in entries {
+            let [p50, p90, p99, max] = match entry.latency {
+                Some(l) => [l.p50_ms, l.p90_ms, l.p99_ms, l.max_ms].map(|ms| format!("{ms}ms")),
+                None => Default::default(),
+            };
+            println!(
+                "{:<width$}  {:>6}  {:>6}  {:>7}  {p50:>7}  {p90:>7}  {p99:>7}  {max:>7}",
+                entry.name, entry.sent, entry.failed, entry.retries
+            );
+            if let Some(error) = &entry.last_error {
+                println!("{:width$}  last error: {}", "", truncate(error, 80));
+            }
+        }
+    }
+
     /// Handle daemon reload command
     pub async fn handle_daemon_reload(&self) -> Result<()> {
         let (pid_file, _) = self.get_daemon_paths()?;
@@ -177,7 +283,7 @@ impl<'a> DaemonHandler<'a> {
                         println!("Daemon reload signal sent successfully");
                     }
                     Ok(DaemonResponse::Error(e)) => {
-                        println!("Daemon reload failed: {}", e);
+                        anyhow::bail!("Daemon reload failed, keeping the previous configuration: {}", e);
                     }
                     Ok(_) => {
                         println!("Daemon reload - unexpected response");
@@ -195,12 +301,152 @@ impl<'a> DaemonHandler<'a> {
         Ok(())
     }
 
+    /// Handle dead-letter queue commands
+    pub async fn handle_dlq(&self, action: DlqAction) -> Result<()> {
+        let (pid_file, socket_path) = self.get_daemon_paths()?;
+        if self.check_daemon_process(&pid_file)?.is_none() {
+            return Err(anyhow::anyhow!(
+                "Daemon is not running. Start it with 'claude-ntfy daemon start'"
+            ));
+        }
+
+        match action {
+            DlqAction::List => {
+                let dead_letters = Self::dead_letters(&socket_path).await?;
+                if dead_letters.is_empty() {
+                    println!("No dead letters");
+                }
+                for dead_letter in dead_letters {
+                    let last_error = dead_letter.attempts.last().map(|a| a.error.as_str()).unwrap_or_default();
+                    println!(
+                        "{}  {}  {}  {} attempt(s)  {}",
+                        dead_letter.id,
+                        dead_letter.task.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                        dead_letter.task.hook_name,
+                        dead_letter.attempts.len(),
+                        truncate(last_error, 80)
+                    );
+                }
+            }
+            DlqAction::Show { id } => {
+                let dead_letter = Self::dead_letters(&socket_path)
+                    .await?
+                    .into_iter()
+                    .find(|dead_letter| dead_letter.id == id)
+                    .ok_or_else(|| anyhow::anyhow!("No dead letter with id {}", id))?;
+                Self::print_dead_letter(&dead_letter)?;
+            }
+            DlqAction::Retry { id, .. } => {
+                let count = Self::dead_letter_count(retry_dead_letters(&socket_path, id).await?)?;
+                println!("Queued {count} dead letter(s) for delivery");
+            }
+            DlqAction::Purge { id, .. } => {
+                let count = Self::dead_letter_count(purge_dead_letters(&socket_path, id).await?)?;
+                println!("Purged {count} dead letter(s)");
+            }
+        }
+        Ok(())
+    }
+
+    /// Print task events until the daemon stops or the command is interrupted
+    pub async fn handle_tail(&self, hook: Option<String>, project: Option<String>, json: bool) -> Result<()> {
+        let (pid_file, socket_path) = self.get_daemon_paths()?;
+        if self.check_daemon_process(&pid_file)?.is_none() {
+            return Err(anyhow::anyhow!(
+                "Daemon is not running. Start it with 'claude-ntfy daemon start'"
+            ));
+        }
+
+        let mut events = subscribe_events(&socket_path, EventFilter { project, hook }).await?;
+        eprintln!("Waiting for daemon events (Ctrl+C to stop)");
+
+        while let Some(event) = events.next().await? {
+            if json {
+                println!("{}", serde_json::to_string(&event)?);
+            } else {
+                Self::print_event(&event);
+            }
+        }
+        Ok(())
+    }
+
+    fn print_event(event: &DaemonEvent) {
+        let (name, details) = match &event.kind {
+            EventKind::Received => ("received", String::new()),
+            EventKind::Rendered { title } => ("rendered", format!("\"{}\"", truncate(title, 60))),
+            EventKind::Sent { backends, latency_ms } => {
+                ("sent", format!("to {} in {} ms", backends.join(", "), latency_ms))
+            }
+            EventKind::Retried { backends, attempt, delay_secs, error } => (
+                "retried",
+                format!(
+                    "attempt {} failed for {}, next in {}s: {}",
+                    attempt,
+                    backends.join(", "),
+                    delay_secs,
+                    truncate(error, 80)
+                ),
+            ),
+            EventKind::Failed { error } => ("failed", truncate(error, 80)),
+        };
+        println!(
+            "{}  {:<8}  {}  {}  {}  {}",
+            event.at.format("%H:%M:%S%.3f"),
+            name,
+            event.hook_name,
+            event.project_path.as_deref().unwrap_or("-"),
+            event.task_id,
+            details
+        );
+    }
+
+    async fn dead_letters(socket_path: &Path) -> Result<Vec<SpooledTask>> {
+        match list_dead_letters(socket_path).await? {
+            DaemonResponse::DeadLetters(dead_letters) => Ok(dead_letters),
+            DaemonResponse::Error(e) => Err(anyhow::anyhow!("Failed to list dead letters: {}", e)),
+            _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+        }
+    }
+
+    fn dead_letter_count(response: DaemonResponse) -> Result<usize> {
+        match response {
+            DaemonResponse::DeadLetterCount(count) => Ok(count),
+            DaemonResponse::Error(e) => Err(anyhow::anyhow!(e)),
+            _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+        }
+    }
+
+    fn print_dead_letter(dead_letter: &SpooledTask) -> Result<()> {
+        let task = &dead_letter.task;
+        println!("Id:       {}", dead_letter.id);
+        println!("Hook:     {}", task.hook_name);
+        println!("Created:  {}", task.timestamp.format("%Y-%m-%d %H:%M:%S"));
+        println!("Project:  {}", task.project_path.as_deref().unwrap_or("-"));
+        println!("Backends: {}", task.backends.join(", "));
+
+        println!("Attempts:");
+        for attempt in &dead_letter.attempts {
+            let backends = if attempt.backends.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attempt.backends.join(", "))
+            };
+            println!("  {}{}: {}", attempt.at.format("%Y-%m-%d %H:%M:%S"), backends, attempt.error);
+        }
+
+        let hook_data: serde_json::Value =
+            serde_json::from_str(&task.hook_data).unwrap_or_else(|_| task.hook_data.clone().into());
+        println!("Hook data:");
+        println!("{}", serde_json::to_string_pretty(&hook_data)?);
+        Ok(())
+    }
+
     /// Start daemon in detached (background) mode
     fn start_daemon_detached(&self) -> Result<()> {
         println!("Starting daemon in detached mode...");
         
         // Create socket path for daemon files
-        let socket_path = create_socket_path(None)?;
+        let socket_path = self.context.daemon_socket_path()?;
         let pid_file = socket_path.with_extension("pid");
         
         // Check if daemon is already running
@@ -223,21 +469,7 @@ impl<'a> DaemonHandler<'a> {
                 .context("Failed to create socket directory")?;
         }
 
-        // Get current executable path
-        let current_exe = std::env::current_exe()
-            .context("Failed to get current executable path")?;
-
-        // Spawn a new process running the daemon in foreground mode
-        // This avoids the tokio runtime nesting issue
-        let mut child = process::Command::new(&current_exe)
-            .arg("daemon")
-            .arg("start")
-            .env("CLAUDE_DAEMON_DETACHED", "1") // Signal to run detached
-            .stdin(process::Stdio::null())
-            .stdout(process::Stdio::null())
-            .stderr(process::Stdio::null())
-            .spawn()
-            .context("Failed to spawn daemon process")?;
+        let mut child = spawn_detached_daemon(&socket_path, self.context.daemon_project())?;
 
         // Wait briefly to see if the child process fails immediately
         std::thread::sleep(std::time::Duration::from_millis(500));
@@ -261,7 +493,7 @@ impl<'a> DaemonHandler<'a> {
         let is_detached = std::env::var("CLAUDE_DAEMON_DETACHED").is_ok();
         
         // Create socket path for daemon files
-        let socket_path = create_socket_path(None)?;
+        let socket_path = self.context.daemon_socket_path()?;
         let pid_file = socket_path.with_extension("pid");
         
         // Only check for existing daemon if this is NOT a detached process spawned by start_daemon_detached()
@@ -297,9 +529,9 @@ impl<'a> DaemonHandler<'a> {
             println!("Starting daemon in foreground...");
         }
         
-        // Write PID file for current process
-        std::fs::write(&pid_file, process::id().to_string())
-            .context("Failed to write PID file")?;
+        // Leave the PID file alone when another daemon owns the socket
+        remove_stale_socket(&socket_path)?;
+        write_pid_file(&pid_file)?;
             
         info!("Daemon started with PID: {}", process::id());
         
@@ -309,8 +541,7 @@ impl<'a> DaemonHandler<'a> {
 
     /// Get daemon file paths (pid_file, socket_path)
     fn get_daemon_paths(&self) -> Result<(PathBuf, PathBuf)> {
-        // Use project-specific socket path if available, otherwise global
-        let socket_path = create_socket_path(self.context.project_path.as_ref())?;
+        let socket_path = self.context.daemon_socket_path()?;
         let pid_file = socket_path.with_extension("pid");
         Ok((pid_file, socket_path))
     }
@@ -339,19 +570,21 @@ impl<'a> DaemonHandler<'a> {
 
     /// Run integrated daemon with IPC server and notification processor
     async fn run_integrated_daemon(&self) -> Result<()> {
-        use crate::daemon::{ipc_server::IpcServer, server::NotificationDaemon};
+        use crate::daemon::{
+            events::EventBus,
+            ipc_server::{IpcServer, ReloadHook},
+            remote::RemoteListener,
+            spool::Spools,
+        };
         use flume::unbounded;
-        use std::sync::{atomic::AtomicUsize, Arc};
 
         // Create communication channels
-        let (task_sender, task_receiver) = unbounded::<NotificationTask>();
         let (shutdown_sender, shutdown_receiver) = unbounded::<()>();
         let (ipc_shutdown_sender, ipc_shutdown_receiver) = unbounded::<()>();
         let (main_shutdown_sender, main_shutdown_receiver) = unbounded::<()>();
-        let queue_size = Arc::new(AtomicUsize::new(0));
 
         // Create socket path
-        let socket_path = create_socket_path(None)?; // Global daemon
+        let socket_path = self.context.daemon_socket_path()?;
         
         // Ensure parent directory exists
         if let Some(parent) = socket_path.parent() {
@@ -359,22 +592,105 @@ impl<'a> DaemonHandler<'a> {
                 .context("Failed to create socket directory")?;
         }
 
-        // Create IPC server
-        let ipc_server = IpcServer::new(
+        // Queue tasks left unfinished by a previous run before accepting new ones
+        let spools = Arc::new(Spools::open_for_socket(&socket_path)?);
+        let events = Arc::new(EventBus::default());
+        let queue = Arc::new(TaskQueue::new(
+            self.context.config_manager.config(),
+            spools.clone(),
+            events.clone(),
+        ));
+        let unfinished = spools.pending.pending()?;
+        if !unfinished.is_empty() {
+            info!("Replaying {} spooled notification(s)", unfinished.len());
+        }
+        for spooled in unfinished {
+            queue.push(spooled);
+        }
+
+        // Create notification daemon
+        let daemon_config = &self.context.config_manager.config().daemon;
+        let watcher = daemon_config.watch_config.then(|| Arc::new(ConfigWatcher::default()));
+        let notification_daemon = Arc::new(NotificationDaemon::new(
+            queue.clone(),
+            shutdown_receiver,
+            spools.clone(),
+            self.context.config_manager.clone(),
+            watcher.clone(),
+            events,
+        )?);
+
+        // Reload on request over IPC or on SIGHUP
+        let reload: ReloadHook = {
+            let config_manager = self.context.config_manager.clone();
+            let queue = queue.clone();
+            let daemon = notification_daemon.clone();
+            let verbose = self.context.verbose;
+            Arc::new(move || reload_config(&config_manager, &queue, &daemon, verbose))
+        };
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let reload = reload.clone();
+            let mut hangups = signal(SignalKind::hangup()).context("Failed to listen for SIGHUP")?;
+            tokio::spawn(async move {
+                while hangups.recv().await.is_some() {
+                    info!("Received SIGHUP, reloading configuration");
+                    if let Err(e) = reload() {
+                        error!("Keeping the previous configuration: {:#}", e);
+                    }
+                }
+            });
+        }
+
+        // Reload the daemon config when it changes, and forget a project config when it does
+        if let Some(watcher) = watcher {
+            let config_path = self.context.config_manager.config_path().to_path_buf();
+            watcher.watch(config_path.clone());
+            let reload = reload.clone();
+            let daemon = notification_daemon.clone();
+            tokio::spawn(async move {
+                watcher
+                    .run(WATCH_INTERVAL, |path| {
+                        if path == config_path {
+                            info!("{} changed, reloading configuration", path.display());
+                            if let Err(e) = reload() {
+                                error!("Keeping the previous configuration: {:#}", e);
+                            }
+                        } else {
+                            info!("{} changed, reloading it for the next task", path.display());
+                            daemon.project_config_changed(path);
+                        }
+                    })
+                    .await
+            });
+        }
+
+        // Create IPC server, listening on TCP too when configured
+        let mut ipc_server = IpcServer::new(
             &socket_path,
-            task_sender,
+            notification_daemon.clone(),
             ipc_shutdown_receiver,
             shutdown_sender.clone(),
-            queue_size.clone(),
             main_shutdown_sender.clone(),
+            spools,
+            reload,
         )?;
+        if let Some(remote) = RemoteListener::bind(daemon_config).await? {
+            ipc_server = ipc_server.with_remote(remote);
+        }
 
-        // Create notification daemon
-        let notification_daemon = NotificationDaemon::new(
-            task_receiver,
-            shutdown_receiver,
-            queue_size.clone(),
-        )?;
+        // Let `daemon status --all` find this daemon
+        let registry = InstanceRegistry::global()?;
+        registry.register(&DaemonInstance {
+            pid: process::id(),
+            socket_path: socket_path.clone(),
+            project_path: self.context.daemon_project().cloned(),
+        })?;
+
+        // Hooks may stop waiting for an autostarted daemon now that it accepts tasks
+        let _ = std::fs::remove_file(socket_path.with_extension("starting"));
 
         info!("Starting integrated daemon components");
 
@@ -417,6 +733,7 @@ impl<'a> DaemonHandler<'a> {
             if pid_file.exists() {
                 let _ = std::fs::remove_file(&pid_file);
             }
+            registry.unregister(process::id());
             info!("Daemon cleanup completed");
         });
 
@@ -447,3 +764,57 @@ impl<'a> DaemonHandler<'a> {
 
 // Implement the handler factory trait to reduce boilerplate
 super::traits::impl_context_handler!(DaemonHandler<'a>);
+
+/// Re-read the daemon's configuration file and apply it to the running daemon
+///
+/// Everything is validated before anything changes, so an invalid file
+/// leaves the daemon running with its previous settings. A daemon started
+/// with `--verbose` keeps debug logging.
+fn reload_config(
+    config_manager: &ConfigManager,
+    queue: &TaskQueue,
+    daemon: &NotificationDaemon,
+    verbose: bool,
+) -> Result<()> {
+    let reloaded = config_manager.reloaded()?;
+    let config = reloaded.config();
+    let log_level = CliContext::parse_log_level(&config.daemon.log_level)?;
+    BackendRegistry::from_configs(&reloaded.backend_configs(), &RetryConfig::default())?;
+
+    if !verbose {
+        CliContext::set_log_level(log_level)?;
+    }
+    queue.reconfigure(config);
+    daemon.reconfigure(Arc::new(reloaded));
+    info!("Reloaded daemon configuration");
+    Ok(())
+}
+
+/// Spawn `claude-ntfy daemon start` as a detached background process
+///
+/// The daemon runs from the directory of `socket_path`, serving `project`
+/// when it is a per-project daemon and using the global configuration otherwise.
+pub(super) fn spawn_detached_daemon(socket_path: &Path, project: Option<&PathBuf>) -> Result<process::Child> {
+    // Get current executable path
+    let current_exe = std::env::current_exe()
+        .context("Failed to get current executable path")?;
+
+    // Spawn a new process running the daemon in foreground mode
+    // This avoids the tokio runtime nesting issue
+    let mut command = process::Command::new(&current_exe);
+    if let Some(project) = project {
+        command.arg("--project").arg(project);
+    }
+    command
+        .arg("daemon")
+        .arg("start")
+        .env("CLAUDE_DAEMON_DETACHED", "1") // Signal to run detached
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null());
+    if let Some(dir) = socket_path.parent() {
+        command.current_dir(dir);
+    }
+
+    command.spawn().context("Failed to spawn daemon process")
+}
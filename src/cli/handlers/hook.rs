@@ -4,16 +4,29 @@
 //! sending to daemon, or processing directly.
 
 use super::super::CliContext;
+use super::daemon::spawn_detached_daemon;
+use crate::daemon::remote::RemoteDaemon;
+use crate::daemon::spool::Spool;
 use crate::daemon::{NotificationTask, NtfyTaskConfig};
-use crate::hooks::{self, DefaultHookProcessor, processor::HookProcessor};
-use crate::ntfy::NtfyMessage;
-use crate::shared::clients::create_sync_client_from_ntfy_config;
-use crate::shared::ipc::convenience::send_notification_task;
-use crate::shared::templates::{MessageFormatter, TemplateEngine, TemplateStyle};
+use crate::hooks::decision::{parse_approval_reply, HookOutput, PermissionDecision, StopOutput};
+use crate::hooks::{self, DefaultHookProcessor, HookFilter, processor::HookProcessor};
+use crate::ntfy::{NtfyAction, NtfyMessage};
+use crate::shared::clients::traits::{HookEvent, NotificationClient, RetryConfig};
+use crate::shared::clients::telegram::TelegramClient;
+use crate::shared::clients::{combine_results, create_async_client_from_ntfy_config, BackendRegistry};
+use crate::shared::config::BackendKind;
+use crate::shared::ipc::convenience::{send_notification_task, send_remote_notification_task};
+use crate::shared::templates::NotificationRenderer;
 use anyhow::{Context, Result};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::io::{self, Read};
-use tracing::{debug, error, info};
+use std::path::Path;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// How long an autostarted daemon gets to come up before hooks try again
+const AUTOSTART_GRACE: Duration = Duration::from_secs(10);
 
 /// Handler for hook processing operations
 pub struct HookHandler<'a> {
@@ -36,6 +49,7 @@ impl<'a> HookHandler<'a> {
         hook_name: Option<String>,
         no_daemon: bool,
         dry_run: bool,
+        remote_approval: bool,
     ) -> Result<()> {
         // Read hook data from stdin (JSON) first
         let mut buffer = String::new();
@@ -81,19 +95,46 @@ impl<'a> HookHandler<'a> {
         let hook_data = processed_hook.enhanced_data.clone();
         debug!("Hook data (after enhancement): {:?}", hook_data);
 
-        if dry_run {
-            println!("Dry run - would send notification:");
-            println!("Hook: {hook_name}");
-            println!("Data: {}", serde_json::to_string_pretty(&hook_data)?);
-            return Ok(());
-        }
-
         // Use configuration from context
         let config_manager = &self.context.config_manager;
 
-        // Check if hook should be processed
+        // Remote approval takes precedence over notification filters, since it
+        // gates the tool call rather than just informing about it
+        if self.requires_remote_approval(&hook_name, &hook_data, remote_approval) {
+            if dry_run {
+                println!("Dry run - would request remote approval:");
+                println!("Hook: {hook_name}");
+                println!("Data: {}", serde_json::to_string_pretty(&hook_data)?);
+                return Ok(());
+            }
+            return self.request_remote_approval(&hook_name, &hook_data).await;
+        }
+
+        // Check if hook should be processed (applies to both daemon and direct paths)
         if !config_manager.should_process_hook(&hook_name, &hook_data) {
             debug!("Hook {} filtered out, skipping", hook_name);
+            if dry_run {
+                println!("Dry run - hook filtered out by configuration:");
+                println!("Hook: {hook_name}");
+            }
+            return Ok(());
+        }
+
+        // A Stop notification that passes the filters waits for an instruction from the phone
+        if hook_name == "Stop" && config_manager.config().reply.enabled {
+            if dry_run {
+                println!("Dry run - would send Stop notification and wait for reply:");
+                println!("Hook: {hook_name}");
+                println!("Data: {}", serde_json::to_string_pretty(&hook_data)?);
+                return Ok(());
+            }
+            return self.handle_stop_reply(&hook_name, &hook_data).await;
+        }
+
+        if dry_run {
+            println!("Dry run - would send notification:");
+            println!("Hook: {hook_name}");
+            println!("Data: {}", serde_json::to_string_pretty(&hook_data)?);
             return Ok(());
         }
 
@@ -102,7 +143,7 @@ impl<'a> HookHandler<'a> {
             self.send_to_daemon(hook_name, hook_data).await?
         } else {
             // Process directly
-            self.process_hook_directly(hook_name, hook_data)?
+            self.process_hook_directly(hook_name, hook_data).await?
         }
 
         Ok(())
@@ -161,32 +202,22 @@ impl<'a> HookHandler<'a> {
         hook_name: String,
         hook_data: Value,
     ) -> Result<()> {
-        use crate::daemon::create_socket_path;
-        
-        // Use global socket path for daemon communication
-        let socket_path = create_socket_path(None)?; // None = global socket
-        
-        // Check if daemon is running (simplified check for now)
-        let pid_file = socket_path.with_extension("pid");
-        if !pid_file.exists() {
-            return Err(anyhow::anyhow!(
-                "Global daemon is not running. Start it with 'claude-ntfy daemon start --global'"
-            ));
-        }
+        use crate::daemon::is_process_running;
 
-        // Get ntfy configuration from project config
-        let config = self.context.config_manager.config();
-        let topic = self.context.config_manager.get_hook_topic(&hook_name);
-        let priority = self.context.config_manager.get_effective_priority(&hook_name, &hook_data);
-
-        // Build ntfy task config from project settings
-        let ntfy_config = NtfyTaskConfig {
-            server_url: config.ntfy.server_url.clone(),
-            topic,
-            priority: Some(priority),
-            tags: config.ntfy.default_tags.clone(),
-            auth_token: config.ntfy.auth_token.clone(),
-        };
+        // The global daemon, or this project's own with `daemon.scope = "project"`
+        let socket_path = self.context.daemon_socket_path()?;
+
+        // Resolve the rendering profile so the daemon renders exactly as the CLI would
+        let ntfy_config = self.task_config(&hook_name, &hook_data);
+
+        // Only names are sent; the daemon resolves them against its configuration
+        let backends: Vec<String> = self
+            .context
+            .config_manager
+            .get_hook_backends(&hook_name)?
+            .into_iter()
+            .map(|backend| backend.name)
+            .collect();
 
         let task = NotificationTask {
             hook_name,
@@ -196,102 +227,376 @@ impl<'a> HookHandler<'a> {
             timestamp: chrono::Local::now(),
             ntfy_config,
             project_path: self.context.project_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            backends,
         };
 
-        // Send to daemon via IPC socket
-        match send_notification_task(&socket_path, task).await {
-            Ok(()) => {
-                debug!("Hook task sent to global daemon successfully");
+        // Prefer a daemon on another host, e.g. when running in a devcontainer
+        if let Some(remote) = RemoteDaemon::from_config(&self.context.config_manager.config().daemon)? {
+            match send_remote_notification_task(&remote, task.clone()).await {
+                Ok(()) => {
+                    debug!("Task sent to remote daemon at {}", remote);
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!("Failed to send hook task to remote daemon at {}, using the local daemon: {:#}", remote, e);
+                }
             }
-            Err(e) => {
-                error!("Failed to send hook task to global daemon: {}", e);
-                return Err(e);
+        }
+
+        // Check if daemon is running
+        let pid_file = socket_path.with_extension("pid");
+        let running = std::fs::read_to_string(&pid_file)
+            .ok()
+            .and_then(|pid| pid.trim().parse().ok())
+            .is_some_and(is_process_running);
+
+        if running {
+            // Send to daemon via IPC socket
+            match send_notification_task(&socket_path, task.clone()).await {
+                Ok(()) => {
+                    debug!("Task sent to daemon at {}", socket_path.display());
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!("Failed to send hook task to daemon: {:#}", e);
+                }
             }
         }
 
-        debug!("Task sent to global daemon");
+        let spool = Spool::open(Spool::dir_for_socket(&socket_path))?;
+        spool.persist(task)?;
+
+        if running {
+            warn!("Notification spooled; the daemon delivers it when restarted");
+        } else if self.context.config_manager.config().daemon.autostart {
+            self.autostart_daemon(&socket_path)?;
+        } else {
+            warn!("Daemon is not running; notification spooled until it is started with 'claude-ntfy daemon start'");
+        }
         Ok(())
     }
 
+    /// Start the daemon in the background so that it drains the spool
+    fn autostart_daemon(&self, socket_path: &Path) -> Result<()> {
+        // A marker keeps hooks firing at the same time from starting several daemons
+        let marker = socket_path.with_extension("starting");
+        let starting = std::fs::metadata(&marker)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified.elapsed().unwrap_or_default() < AUTOSTART_GRACE);
+        if starting {
+            debug!("Daemon is already starting; notification spooled");
+            return Ok(());
+        }
+
+        std::fs::write(&marker, std::process::id().to_string())
+            .context("Failed to write daemon start marker")?;
+        match spawn_detached_daemon(socket_path, self.context.daemon_project()) {
+            Ok(child) => info!("Started daemon with PID {}; notification spooled", child.id()),
+            Err(e) => warn!("Failed to start daemon, notification spooled: {:#}", e),
+        }
+        Ok(())
+    }
 
     /// Process hook directly without daemon
-    fn process_hook_directly(
+    async fn process_hook_directly(
         &self,
         hook_name: String,
         hook_data: Value,
     ) -> Result<()> {
+        // Create clients for the backends this hook is routed to
+        let task_config = self.task_config(&hook_name, &hook_data);
+        let backends = self.context.config_manager.get_hook_backends(&hook_name)?;
+        let registry = BackendRegistry::from_configs(&backends, &task_config.retry_config())?;
+
+        let message = task_config.render_message(&NotificationRenderer::new()?, &hook_name, &hook_data)?;
+        let event = HookEvent::new(&hook_name, hook_data);
+
+        // Send notification to every backend
+        combine_results(registry.send(&message, &event).await)
+            .context("Failed to send notification")?;
+
+        info!("Notification sent successfully for hook: {}", hook_name);
+        Ok(())
+    }
+
+    /// Resolve the per-project rendering and delivery profile for a hook
+    fn task_config(&self, hook_name: &str, hook_data: &Value) -> NtfyTaskConfig {
         let config_manager = &self.context.config_manager;
         let config = config_manager.config();
+        let templates = &config.templates;
+
+        let mut task_config = NtfyTaskConfig::new(&config.ntfy.server_url, config_manager.get_hook_topic(hook_name));
+        task_config.priority = Some(config_manager.get_effective_priority(hook_name, hook_data));
+        task_config.tags = config.ntfy.default_tags.clone();
+        task_config.auth_token = config.ntfy.auth_token.clone();
+        task_config.timeout_secs = config.ntfy.timeout_secs;
+        task_config.custom_template = templates
+            .use_custom
+            .then(|| templates.custom_templates.get(hook_name).cloned())
+            .flatten();
+        task_config.variables = templates.variables.clone();
+        task_config.style = templates.style;
+        task_config.retry_attempts = config.retry.attempts;
+        task_config.retry_base_delay_ms = config.retry.base_delay_ms;
+        task_config
+    }
 
-        // Create ntfy client using unified factory
-        let client = create_sync_client_from_ntfy_config(&config.ntfy)?;
-
-        // Create template engine and formatter
-        let template_engine = TemplateEngine::new_with_style(TemplateStyle::Rich)?;
-        let formatter = MessageFormatter::default();
-
-        // Prepare message - use hook name directly (no transformation needed)
-        let template_name = &hook_name;
-        let formatted_data = template_engine.format_hook_data(&hook_name, &hook_data);
-
-        let body = if config.templates.use_custom {
-            if let Some(custom_template) = config.templates.custom_templates.get(&hook_name) {
-                let mut hb = handlebars::Handlebars::new();
-                hb.set_strict_mode(false);
-                hb.render_template(custom_template, &formatted_data)
-                    .unwrap_or_else(|e| {
-                        error!("Failed to render custom template: {}", e);
-                        template_engine
-                            .render(
-                                &template_name,
-                                &formatted_data,
-                            )
-                            .unwrap_or_else(|_| format!("Hook: {hook_name}"))
-                    })
-            } else {
-                template_engine.render(
-                    &template_name,
-                    &formatted_data,
-                )?
+    /// Render the notification message for a hook from project configuration
+    fn build_message(&self, hook_name: &str, hook_data: &Value) -> Result<NtfyMessage> {
+        self.task_config(hook_name, hook_data)
+            .render_message(&NotificationRenderer::new()?, hook_name, hook_data)
+    }
+
+    /// Check whether a hook must wait for a remote Allow/Deny decision
+    fn requires_remote_approval(&self, hook_name: &str, hook_data: &Value, forced: bool) -> bool {
+        let config = self.context.config_manager.config();
+        if hook_name != "PreToolUse" || !config.hooks.enabled || !(forced || config.approval.enabled) {
+            return false;
+        }
+
+        // Approval tools use the filter rule syntax; an empty list matches every tool
+        let rules = HashMap::from([(hook_name.to_string(), config.approval.tools.clone())]);
+        HookFilter::from_config(&rules, false).allows(hook_name, hook_data)
+    }
+
+    /// Ask for approval via ntfy action buttons and print the decision for Claude Code
+    ///
+    /// Any failure along the way falls back to the configured timeout decision,
+    /// so a broken ntfy server never blocks the session.
+    async fn request_remote_approval(&self, hook_name: &str, hook_data: &Value) -> Result<()> {
+        let approval = &self.context.config_manager.config().approval;
+        let request_id = format!("{:016x}", rand::random::<u64>());
+
+        let (decision, reason) = match self.wait_for_approval(hook_name, hook_data, &request_id).await {
+            Ok(Some(decision)) => {
+                info!("Remote approval {} for request {}", decision.as_str(), request_id);
+                (decision, format!("Remote decision '{}' via ntfy", decision.as_str()))
+            }
+            Ok(None) => {
+                info!("Remote approval timed out for request {}", request_id);
+                (
+                    approval.timeout_decision,
+                    format!("No remote decision within {}s", approval.timeout_secs),
+                )
+            }
+            Err(e) => {
+                error!("Remote approval failed: {:#}", e);
+                (approval.timeout_decision, format!("Remote approval failed: {e}"))
+            }
+        };
+
+        let output = HookOutput::pre_tool_use(decision, reason);
+        println!("{}", serde_json::to_string(&output)?);
+        Ok(())
+    }
+
+    /// Publish the approval request and wait for a matching reply
+    ///
+    /// Returns `Ok(None)` if no decision arrives before the timeout.
+    async fn wait_for_approval(
+        &self,
+        hook_name: &str,
+        hook_data: &Value,
+        request_id: &str,
+    ) -> Result<Option<PermissionDecision>> {
+        let config = self.context.config_manager.config();
+        let approval = &config.approval;
+
+        let reply_topic = approval
+            .reply_topic
+            .clone()
+            .unwrap_or_else(|| format!("{}-replies", config.ntfy.default_topic));
+        let reply_url = format!("{}/{}", config.ntfy.server_url.trim_end_matches('/'), reply_topic);
+
+        let tool_name = hook_data
+            .get("tool_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("tool");
+        let mut message = self.build_message(hook_name, hook_data)?;
+        message.title = Some(format!("Approval needed: {tool_name}"));
+        message.priority = Some(config.hooks.decision_hook_priority);
+        message.actions = Some(vec![
+            approval_action("Allow", PermissionDecision::Allow, &reply_url, request_id, &approval.reply_token),
+            approval_action("Deny", PermissionDecision::Deny, &reply_url, request_id, &approval.reply_token),
+        ]);
+
+        let timeout = Duration::from_secs(approval.timeout_secs);
+        if let Some(telegram) = self.reply_channel(approval.backend.as_deref())? {
+            debug!("Sending approval request {} via Telegram", request_id);
+            return telegram
+                .send_and_wait(&message, timeout, |reply| parse_approval_reply(reply, request_id))
+                .await;
+        }
+
+        debug!("Sending approval request {}, replies on topic {}", request_id, reply_topic);
+        self.publish_and_wait(&message, &reply_topic, timeout, |reply| {
+            parse_approval_reply(reply, request_id)
+        })
+        .await
+    }
+
+    /// Send the Stop notification with a Reply action and wait for a text reply
+    ///
+    /// If a reply arrives within the configured window, prints the Stop-hook
+    /// block decision so Claude Code continues with the reply as its instruction.
+    async fn handle_stop_reply(&self, hook_name: &str, hook_data: &Value) -> Result<()> {
+        let config = self.context.config_manager.config();
+        let reply = &config.reply;
+
+        let session_id = hook_data
+            .get("session_id")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()));
+        let prefix = reply
+            .topic_prefix
+            .clone()
+            .unwrap_or_else(|| format!("{}-reply", config.ntfy.default_topic));
+        let reply_topic = session_reply_topic(&prefix, &session_id);
+        let reply_url = format!("{}/{}", config.ntfy.server_url.trim_end_matches('/'), reply_topic);
+
+        let mut message = self.build_message(hook_name, hook_data)?;
+        message.actions = Some(vec![NtfyAction {
+            action: "view".to_string(),
+            label: "Reply".to_string(),
+            url: Some(reply_url),
+            method: None,
+            headers: None,
+            body: None,
+            clear: Some(true),
+        }]);
+
+        // Telegram replies are plain messages in the chat, so no Reply button is needed
+        let telegram = self.reply_channel(reply.backend.as_deref())?;
+        if telegram.is_some() {
+            message.actions = None;
+        }
+
+        if reply.wait_secs == 0 {
+            let result = match &telegram {
+                Some(telegram) => telegram.send(&message).await,
+                None => create_async_client_from_ntfy_config(&config.ntfy)?.send(&message).await,
+            };
+            return result.context("Failed to send Stop notification");
+        }
+
+        let wait = Duration::from_secs(reply.wait_secs);
+        let matcher = |text: &str| {
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        };
+        let result = match &telegram {
+            Some(telegram) => telegram.send_and_wait(&message, wait, matcher).await,
+            None => self.publish_and_wait(&message, &reply_topic, wait, matcher).await,
+        };
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                // Letting Claude stop is the safe default if the reply channel is broken
+                error!("Stop reply failed: {:#}", e);
+                None
             }
-        } else {
-            template_engine.render(
-                &template_name,
-                &formatted_data,
-            )?
         };
 
-        let title = formatter.format_title(&hook_name, &formatted_data);
-        let topic = config_manager.get_hook_topic(&hook_name);
-        let priority = config_manager.get_effective_priority(&hook_name, &hook_data);
-        let mut tags = formatter.get_tags(&hook_name);
-        if tags.is_empty() {
-            tags = config.ntfy.default_tags.clone().unwrap_or_default();
+        match text {
+            Some(text) => {
+                info!("Received reply for session {}, continuing", session_id);
+                println!("{}", serde_json::to_string(&StopOutput::block(text))?);
+            }
+            None => debug!("No reply for session {} within {}s", session_id, reply.wait_secs),
         }
+        Ok(())
+    }
 
-        let message = NtfyMessage {
-            topic,
-            title: Some(title),
-            message: body,
-            priority: Some(priority),
-            tags: Some(tags),
-            click: None,
-            attach: None,
-            filename: None,
-            delay: None,
-            email: None,
-            call: None,
-            actions: None,
-            markdown: Some(true),
+    /// Resolve the Telegram backend configured as a reply channel, if any
+    fn reply_channel(&self, backend: Option<&str>) -> Result<Option<TelegramClient>> {
+        let Some(name) = backend else {
+            return Ok(None);
         };
 
-        // Send notification
+        let backend = self
+            .context
+            .config_manager
+            .backend_configs()
+            .into_iter()
+            .find(|b| b.name == name)
+            .with_context(|| format!("Unknown reply backend '{name}'"))?;
+        match backend.kind {
+            BackendKind::Telegram(telegram) => Ok(Some(TelegramClient::new(telegram, RetryConfig::default())?)),
+            other => anyhow::bail!(
+                "Backend '{name}' is a {} backend; only telegram backends can receive replies",
+                other.type_name()
+            ),
+        }
+    }
+
+    /// Publish a message and wait for the first reply on `reply_topic` accepted by `matcher`
+    ///
+    /// Returns `Ok(None)` if nothing matching arrives before the timeout.
+    async fn publish_and_wait<T>(
+        &self,
+        message: &NtfyMessage,
+        reply_topic: &str,
+        timeout: Duration,
+        mut matcher: impl FnMut(&str) -> Option<T>,
+    ) -> Result<Option<T>> {
+        let config = self.context.config_manager.config();
+        let client = create_async_client_from_ntfy_config(&config.ntfy)?;
+
+        // Subscribe before publishing so a fast reply cannot be missed
+        let mut subscription = client
+            .subscribe(reply_topic, timeout + Duration::from_secs(5))
+            .await?;
+
         client
-            .send(&message)
+            .send(message)
+            .await
             .context("Failed to send notification")?;
 
-        info!("Notification sent successfully for hook: {}", hook_name);
-        Ok(())
+        let wait = async {
+            while let Some(reply) = subscription.next_message().await? {
+                if let Some(value) = matcher(&reply) {
+                    return Ok(Some(value));
+                }
+            }
+            Ok(None)
+        };
+
+        tokio::time::timeout(timeout, wait).await.unwrap_or(Ok(None))
+    }
+}
+
+/// Build a per-session reply topic, keeping only characters ntfy accepts
+fn session_reply_topic(prefix: &str, session_id: &str) -> String {
+    let session: String = session_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(36)
+        .collect();
+    format!("{prefix}-{session}")
+}
+
+/// Build an ntfy http action that posts `<decision> <request_id>` to the reply topic
+///
+/// The action is stored with the message and readable by every subscriber of
+/// the topic, so it only ever carries the publish-only `reply_token`.
+fn approval_action(
+    label: &str,
+    decision: PermissionDecision,
+    reply_url: &str,
+    request_id: &str,
+    reply_token: &Option<String>,
+) -> NtfyAction {
+    NtfyAction {
+        action: "http".to_string(),
+        label: label.to_string(),
+        url: Some(reply_url.to_string()),
+        method: Some("POST".to_string()),
+        headers: reply_token
+            .as_ref()
+            .map(|token| HashMap::from([("Authorization".to_string(), format!("Bearer {token}"))])),
+        body: Some(format!("{} {}", decision.as_str(), request_id)),
+        clear: Some(true),
     }
 }
 
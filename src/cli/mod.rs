@@ -10,7 +10,7 @@ pub mod context;
 use anyhow::Result;
 use clap::Parser;
 
-pub use commands::{Cli, Commands, ConfigAction, DaemonAction};
+pub use commands::{Cli, Commands, ConfigAction, DaemonAction, DlqAction};
 pub use handlers::CommandHandler;
 pub use context::CliContext;
 
@@ -32,10 +32,11 @@ impl CliApp {
         let handler = CommandHandler::new(context);
 
         // Handle default hook mode when called without subcommand
-        let command = cli.command.unwrap_or_else(|| Commands::Hook {
+        let command = cli.command.unwrap_or(Commands::Hook {
             hook_name: None,
             no_daemon: false,
             dry_run: false,
+            remote_approval: false,
         });
 
         // Execute the command through handlers
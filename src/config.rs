@@ -1,10 +1,14 @@
 use crate::errors::{AppError, AppResult};
+use crate::hooks::HookFilter;
 use directories::BaseDirs;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 // Re-export shared types for convenience
-pub use crate::shared::config::{Config, NtfyConfig};
+pub use crate::shared::config::{BackendConfig, BackendKind, Config, NtfyBackendConfig, NtfyConfig};
+
+/// Name of the backend built from the `[ntfy]` section
+pub const DEFAULT_BACKEND: &str = "ntfy";
 
 /// Configuration manager for the Claude Code Ntfy Service
 ///
@@ -163,6 +167,47 @@ impl ConfigManager {
         }
     }
 
+    /// Loads the configuration file again, leaving this instance unchanged
+    ///
+    /// Used by the daemon to pick up edits without restarting. Unlike
+    /// [`new()`](Self::new), a missing file is an error rather than being
+    /// recreated with defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or if
+    /// `[hooks.routes]` names a backend that does not exist.
+    pub fn reloaded(&self) -> AppResult<Self> {
+        Self::load(self.config_path.clone())
+    }
+
+    /// Loads an existing configuration file without creating anything
+    ///
+    /// Used by the daemon to read the configuration of a project it delivers
+    /// for.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or if
+    /// `[hooks.routes]` names a backend that does not exist.
+    pub fn load(config_path: PathBuf) -> AppResult<Self> {
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| AppError::io_with_source(&config_path, "read config file", e))?;
+        let config = toml::from_str(&content)
+            .map_err(|e| AppError::config_with_source("Failed to parse config file", e))?;
+        let loaded = ConfigManager { config_path, config };
+
+        for hook_name in loaded.config.hooks.routes.keys() {
+            loaded.get_hook_backends(hook_name)?;
+        }
+        Ok(loaded)
+    }
+
+    /// Path of a project's configuration file, whether or not it exists
+    pub fn project_config_path(project_path: &Path) -> PathBuf {
+        project_path.join(".claude").join("ntfy-service").join("config.toml")
+    }
+
     /// Saves the current configuration to disk
     ///
     /// Writes the configuration back to the TOML file it was loaded from.
@@ -181,6 +226,11 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Returns the path of the configuration file in use
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
     /// Returns an immutable reference to the configuration
     ///
     /// Provides read-only access to the loaded configuration.
@@ -217,9 +267,82 @@ impl ConfigManager {
             .unwrap_or_else(|| self.config.ntfy.default_topic.clone())
     }
 
+    /// Returns every configured notification backend
+    ///
+    /// The `[ntfy]` section is always included as the `ntfy` backend, unless
+    /// a `[[backends]]` entry redefines that name.
+    pub fn backend_configs(&self) -> Vec<BackendConfig> {
+        let mut backends = vec![BackendConfig {
+            name: DEFAULT_BACKEND.to_string(),
+            kind: BackendKind::Ntfy(NtfyBackendConfig {
+                server_url: self.config.ntfy.server_url.clone(),
+                topic: None,
+                auth_token: self.config.ntfy.auth_token.clone(),
+                timeout_secs: self.config.ntfy.timeout_secs,
+            }),
+        }];
+
+        for backend in &self.config.backends {
+            backends.retain(|existing| existing.name != backend.name);
+            backends.push(backend.clone());
+        }
+
+        backends
+    }
+
+    /// Gets the backends a hook is routed to
+    ///
+    /// Uses the hook's entry in `[hooks.routes]`, then the `*` entry, and
+    /// falls back to the `ntfy` backend when neither exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if a route names an unknown backend.
+    pub fn get_hook_backends(&self, hook_name: &str) -> AppResult<Vec<BackendConfig>> {
+        let routes = &self.config.hooks.routes;
+        let names = routes
+            .get(hook_name)
+            .or_else(|| routes.get("*"))
+            .cloned()
+            .unwrap_or_else(|| vec![DEFAULT_BACKEND.to_string()]);
+
+        self.backends_named(&names)
+    }
+
+    /// Looks up backends by name
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error if a name matches no backend.
+    pub fn backends_named(&self, names: &[String]) -> AppResult<Vec<BackendConfig>> {
+        let backends = self.backend_configs();
+        names
+            .iter()
+            .map(|name| {
+                backends
+                    .iter()
+                    .find(|backend| &backend.name == name)
+                    .cloned()
+                    .ok_or_else(|| AppError::config(format!("Unknown backend '{name}'")))
+            })
+            .collect()
+    }
+
     /// Determines whether a hook should be processed based on configuration
-    pub fn should_process_hook(&self, _hook_name: &str, _hook_data: &serde_json::Value) -> bool {
-        self.config.hooks.enabled
+    ///
+    /// Applies the global `hooks.enabled` switch and then the per-hook
+    /// `[hooks.filters]` rules. Decision hooks bypass the filters when
+    /// `never_filter_decision_hooks` is set.
+    pub fn should_process_hook(&self, hook_name: &str, hook_data: &serde_json::Value) -> bool {
+        if !self.config.hooks.enabled {
+            return false;
+        }
+
+        HookFilter::from_config(
+            &self.config.hooks.filters,
+            self.config.hooks.never_filter_decision_hooks,
+        )
+        .allows(hook_name, hook_data)
     }
     
     /// Get effective priority for a hook, considering decision-requiring status
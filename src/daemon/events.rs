@@ -0,0 +1,48 @@
+//! Live task events for `claude-ntfy tail`
+//!
+//! The daemon publishes an event whenever a task is received, rendered,
+//! sent, retried or given up on. Subscribers connected over IPC each get
+//! their own copy; events published while nobody listens are dropped.
+
+use tokio::sync::broadcast;
+
+use super::shared::{DaemonEvent, EventKind};
+use super::spool::SpooledTask;
+
+/// Events a slow subscriber may fall behind by before it misses some
+const EVENT_BUFFER: usize = 256;
+
+/// Fans task events out to every subscriber
+pub struct EventBus {
+    sender: broadcast::Sender<DaemonEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+}
+
+impl EventBus {
+    /// Publish an event about a task
+    pub fn publish(&self, spooled: &SpooledTask, kind: EventKind) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        let _ = self.sender.send(DaemonEvent {
+            at: chrono::Local::now(),
+            task_id: spooled.id.clone(),
+            hook_name: spooled.task.hook_name.clone(),
+            project_path: spooled.task.project_path.clone(),
+            kind,
+        });
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DaemonEvent> {
+        self.sender.subscribe()
+    }
+}
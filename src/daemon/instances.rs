@@ -0,0 +1,121 @@
+//! Running daemons, for `claude-ntfy daemon status --all`
+//!
+//! Per-project daemons listen in their project's directory, so every daemon
+//! records itself in `~/.claude/ntfy-service/daemons/` while it runs. Records
+//! left behind by daemons that died without cleaning up are removed the next
+//! time the daemons are listed.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::create_socket_path;
+use super::server::is_process_running;
+
+/// A daemon as recorded while it runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonInstance {
+    pub pid: u32,
+    pub socket_path: PathBuf,
+    /// Project served by a per-project daemon; `None` for the global daemon
+    pub project_path: Option<PathBuf>,
+}
+
+/// Directory of daemon records
+pub struct InstanceRegistry {
+    dir: PathBuf,
+}
+
+impl InstanceRegistry {
+    /// Open the registry in `dir`, creating it if needed
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create daemon registry {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// The registry shared by every daemon of this user
+    pub fn global() -> Result<Self> {
+        let socket_path = create_socket_path(None)?;
+        Self::open(socket_path.with_file_name("daemons"))
+    }
+
+    /// Record a running daemon
+    pub fn register(&self, instance: &DaemonInstance) -> Result<()> {
+        let data = serde_json::to_vec_pretty(instance).context("Failed to serialize daemon record")?;
+        let path = self.path(instance.pid);
+        std::fs::write(&path, data)
+            .with_context(|| format!("Failed to write daemon record {}", path.display()))
+    }
+
+    /// Remove the record of a daemon that is stopping
+    pub fn unregister(&self, pid: u32) {
+        let _ = std::fs::remove_file(self.path(pid));
+    }
+
+    /// Daemons that are still running, ordered by PID
+    pub fn running(&self) -> Result<Vec<DaemonInstance>> {
+        let entries = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read daemon registry {}", self.dir.display()))?;
+
+        let mut instances = Vec::new();
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let instance = std::fs::read(&path)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<DaemonInstance>(&data).ok());
+            match instance {
+                Some(instance) if is_process_running(instance.pid) && instance.socket_path.exists() => {
+                    instances.push(instance)
+                }
+                _ => {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+        instances.sort_by_key(|instance| instance.pid);
+        Ok(instances)
+    }
+
+    fn path(&self, pid: u32) -> PathBuf {
+        self.dir.join(format!("{pid}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stale_records_are_pruned() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = InstanceRegistry::open(temp_dir.path().join("daemons")).unwrap();
+        let socket_path = temp_dir.path().join("daemon.sock");
+        std::fs::write(&socket_path, "").unwrap();
+
+        let live = DaemonInstance {
+            pid: std::process::id(),
+            socket_path: socket_path.clone(),
+            project_path: Some(temp_dir.path().to_path_buf()),
+        };
+        registry.register(&live).unwrap();
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        exited.wait().unwrap();
+        registry
+            .register(&DaemonInstance {
+                pid: exited.id(),
+                socket_path,
+                project_path: None,
+            })
+            .unwrap();
+
+        let running = registry.running().unwrap();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].pid, live.pid);
+        assert!(!registry.path(exited.id()).exists());
+
+        registry.unregister(live.pid);
+        assert!(registry.running().unwrap().is_empty());
+    }
+}
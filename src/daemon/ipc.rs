@@ -1,9 +1,15 @@
 //! IPC utilities for daemon communication
 //!
-//! This module provides utilities for daemon socket path creation.
+//! This module provides utilities for daemon socket path creation and for
+//! keeping the socket and PID file private to the user running the daemon.
 
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::fs::{OpenOptions, Permissions};
+use std::io::Write;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::shared::config::{DaemonConfig, DaemonScope};
 
 /// Create socket path for daemon communication
 /// Reused from daemon_shared for compatibility
@@ -18,4 +24,138 @@ pub fn create_socket_path(project_path: Option<&PathBuf>) -> Result<PathBuf> {
     std::fs::create_dir_all(&base_path).context("Failed to create socket directory")?;
 
     Ok(base_path.join("daemon.sock"))
-}
\ No newline at end of file
+}
+
+/// Project whose own daemon serves `project_path`; `None` for the global daemon
+pub fn daemon_project<'a>(config: &DaemonConfig, project_path: Option<&'a PathBuf>) -> Option<&'a PathBuf> {
+    match config.scope {
+        DaemonScope::Global => None,
+        DaemonScope::Project => project_path,
+    }
+}
+
+/// Socket of the daemon serving `project_path` with the given daemon settings
+///
+/// Every client and the daemon itself resolve the socket this way, so they
+/// agree on which daemon is meant.
+pub fn daemon_socket_path(config: &DaemonConfig, project_path: Option<&PathBuf>) -> Result<PathBuf> {
+    create_socket_path(daemon_project(config, project_path))
+}
+
+/// Write the PID of this process to `pid_file`, readable by its owner only
+pub fn write_pid_file(pid_file: &Path) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(pid_file)
+        .and_then(|mut file| {
+            // The mode only applies to newly created files
+            file.set_permissions(Permissions::from_mode(0o600))?;
+            write!(file, "{}", std::process::id())?;
+            Ok(file)
+        })
+        .with_context(|| format!("Failed to write PID file {}", pid_file.display()))?;
+    file.flush().context("Failed to write PID file")
+}
+
+/// Restrict the daemon socket to its owner
+pub fn restrict_socket(socket_path: &Path) -> Result<()> {
+    std::fs::set_permissions(socket_path, Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to restrict permissions of {}", socket_path.display()))
+}
+
+/// Remove a socket left behind by a daemon that is no longer running
+///
+/// Fails without touching the socket when a daemon still listens on it or
+/// when it belongs to another user.
+pub fn remove_stale_socket(socket_path: &Path) -> Result<()> {
+    let metadata = match std::fs::symlink_metadata(socket_path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        metadata => metadata.with_context(|| format!("Failed to inspect {}", socket_path.display()))?,
+    };
+
+    let owner = current_uid();
+    if metadata.uid() != owner {
+        return Err(anyhow::anyhow!(
+            "{} belongs to uid {}, not to this user (uid {}); refusing to replace it",
+            socket_path.display(),
+            metadata.uid(),
+            owner
+        ));
+    }
+    if std::os::unix::net::UnixStream::connect(socket_path).is_ok() {
+        return Err(anyhow::anyhow!(
+            "Another daemon is already listening on {}",
+            socket_path.display()
+        ));
+    }
+
+    std::fs::remove_file(socket_path).context("Failed to remove existing socket file")
+}
+
+/// Check that a local client runs as the same user as the daemon
+pub fn authorize_peer(stream: &tokio::net::UnixStream) -> Result<()> {
+    let peer = stream.peer_cred().context("Failed to read peer credentials")?;
+    let owner = current_uid();
+    if peer.uid() != owner {
+        let pid = peer.pid().map_or_else(|| "unknown".to_string(), |pid| pid.to_string());
+        return Err(anyhow::anyhow!(
+            "uid {} (pid {}) is not the daemon owner (uid {})",
+            peer.uid(),
+            pid,
+            owner
+        ));
+    }
+    Ok(())
+}
+
+fn current_uid() -> u32 {
+    unsafe { libc::geteuid() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_socket_follows_scope() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().to_path_buf();
+        let project_socket = project.join(".claude/ntfy-service/daemon.sock");
+        let mut config = crate::shared::config::Config::default().daemon;
+        config.scope = DaemonScope::Project;
+        assert_eq!(daemon_socket_path(&config, Some(&project)).unwrap(), project_socket);
+        assert!(project_socket.parent().unwrap().is_dir());
+    }
+
+    #[test]
+    fn test_only_stale_sockets_are_removed() {
+        let temp_dir = TempDir::new().unwrap();
+        let socket_path = temp_dir.path().join("daemon.sock");
+        remove_stale_socket(&socket_path).unwrap();
+
+        let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let error = remove_stale_socket(&socket_path).unwrap_err();
+        assert!(error.to_string().contains("already listening"), "{error}");
+        assert!(socket_path.exists());
+
+        drop(listener);
+        remove_stale_socket(&socket_path).unwrap();
+        assert!(!socket_path.exists());
+    }
+
+    #[test]
+    fn test_pid_file_is_private() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("daemon.pid");
+        std::fs::write(&pid_file, "1").unwrap();
+        std::fs::set_permissions(&pid_file, Permissions::from_mode(0o644)).unwrap();
+
+        write_pid_file(&pid_file).unwrap();
+        assert_eq!(std::fs::read_to_string(&pid_file).unwrap(), std::process::id().to_string());
+        assert_eq!(std::fs::metadata(&pid_file).unwrap().mode() & 0o777, 0o600);
+    }
+}
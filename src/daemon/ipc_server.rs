@@ -1,24 +1,44 @@
 //! IPC Server for daemon communication
 //!
-//! This module provides a Unix socket server for handling daemon IPC communication.
+//! This module provides a Unix socket server for handling daemon IPC communication,
+//! and optionally serves the same protocol to remote clients over TCP.
 
 use anyhow::{Context, Result};
 use flume::{Receiver, Sender};
-use std::sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{UnixListener, UnixStream};
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+use tokio::net::{TcpStream, UnixListener};
+use tokio_rustls::TlsAcceptor;
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info, warn};
 
-use super::shared::{DaemonMessage, DaemonResponse, NotificationTask};
+use super::ipc::{authorize_peer, remove_stale_socket, restrict_socket};
+use super::protocol::{Connection, CAPABILITIES, REMOTE_CAPABILITIES};
+use super::remote::{secure, RemoteListener, Stream};
+use super::shared::{DaemonMessage, DaemonResponse, EventFilter, EventKind};
+use super::server::NotificationDaemon;
+use super::spool::{SpooledTask, Spools};
+
+/// Re-reads and applies the daemon configuration
+///
+/// Fails without changing anything when the new configuration is invalid.
+pub type ReloadHook = Arc<dyn Fn() -> Result<()> + Send + Sync>;
 
 /// IPC server for handling daemon communication
 pub struct IpcServer {
     listener: UnixListener,
-    task_sender: Sender<NotificationTask>,
+    remote: Option<RemoteListener>,
     shutdown_receiver: Receiver<()>,
+    handler: ClientHandler,
+}
+
+/// Daemon state shared with every client connection
+#[derive(Clone)]
+struct ClientHandler {
+    daemon: Arc<NotificationDaemon>,
     shutdown_sender: Sender<()>,
     main_shutdown_sender: Sender<()>,
-    queue_size: Arc<AtomicUsize>,
+    spools: Arc<Spools>,
+    reload: ReloadHook,
     is_running: Arc<AtomicBool>,
     start_time: std::time::Instant,
 }
@@ -27,36 +47,45 @@ impl IpcServer {
     /// Create new IPC server
     pub fn new(
         socket_path: &std::path::Path,
-        task_sender: Sender<NotificationTask>,
+        daemon: Arc<NotificationDaemon>,
         shutdown_receiver: Receiver<()>,
         shutdown_sender: Sender<()>,
-        queue_size: Arc<AtomicUsize>,
         main_shutdown_sender: Sender<()>,
+        spools: Arc<Spools>,
+        reload: ReloadHook,
     ) -> Result<Self> {
-        // Remove existing socket file if it exists
-        if socket_path.exists() {
-            std::fs::remove_file(socket_path)
-                .context("Failed to remove existing socket file")?;
-        }
+        // Replace a socket left behind by a crash, but never a live daemon's
+        remove_stale_socket(socket_path)?;
 
-        // Create socket listener
+        // Create socket listener; clients are also checked by uid as they connect
         let listener = UnixListener::bind(socket_path)
             .context("Failed to bind Unix socket")?;
+        restrict_socket(socket_path)?;
 
         info!("IPC server bound to socket: {}", socket_path.display());
 
         Ok(IpcServer {
             listener,
-            task_sender,
+            remote: None,
             shutdown_receiver,
-            shutdown_sender,
-            main_shutdown_sender,
-            queue_size,
-            is_running: Arc::new(AtomicBool::new(true)),
-            start_time: std::time::Instant::now(),
+            handler: ClientHandler {
+                daemon,
+                shutdown_sender,
+                main_shutdown_sender,
+                spools,
+                reload,
+                is_running: Arc::new(AtomicBool::new(true)),
+                start_time: std::time::Instant::now(),
+            },
         })
     }
 
+    /// Also serve authenticated clients on a TCP listener
+    pub fn with_remote(mut self, remote: RemoteListener) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
     /// Run the IPC server
     pub async fn run(self) -> Result<()> {
         info!("IPC server started");
@@ -73,18 +102,15 @@ impl IpcServer {
                 result = self.listener.accept() => {
                     match result {
                         Ok((stream, _addr)) => {
+                            if let Err(e) = authorize_peer(&stream) {
+                                warn!("Rejected IPC client: {:#}", e);
+                                continue;
+                            }
                             debug!("New IPC client connection");
-                            let task_sender = self.task_sender.clone();
-                            let shutdown_sender = self.shutdown_sender.clone();
-                            let main_shutdown_sender = self.main_shutdown_sender.clone();
-                            let queue_size = self.queue_size.clone();
-                            let is_running = self.is_running.clone();
-                            let start_time = self.start_time;
+                            let handler = self.handler.clone();
 
                             tokio::spawn(async move {
-                                if let Err(e) = Self::handle_client(
-                                    stream, task_sender, shutdown_sender, main_shutdown_sender, queue_size, is_running, start_time
-                                ).await {
+                                if let Err(e) = handler.handle_client(stream, None).await {
                                     error!("Error handling IPC client: {}", e);
                                 }
                             });
@@ -94,112 +120,198 @@ impl IpcServer {
                         }
                     }
                 }
+
+                // Handle remote clients, which must authenticate
+                result = accept_remote(self.remote.as_ref()) => {
+                    match result {
+                        Ok((stream, peer, tls, token)) => {
+                            debug!("New remote client connection from {}", peer);
+                            let handler = self.handler.clone();
+
+                            tokio::spawn(async move {
+                                let handled = match secure(stream, tls).await {
+                                    Ok(stream) => handler.handle_client(stream, Some(&token)).await,
+                                    Err(e) => Err(e),
+                                };
+                                if let Err(e) = handled {
+                                    warn!("Error handling remote client {}: {:#}", peer, e);
+                                }
+                            });
+                        }
+                        Err(e) => {
+                            error!("{:#}", e);
+                        }
+                    }
+                }
             }
         }
 
-        self.is_running.store(false, Ordering::Relaxed);
+        self.handler.is_running.store(false, Ordering::Relaxed);
         info!("IPC server stopped");
         Ok(())
     }
+}
 
+impl ClientHandler {
     /// Handle individual client connection
-    async fn handle_client(
-        mut stream: UnixStream,
-        task_sender: Sender<NotificationTask>,
-        shutdown_sender: Sender<()>,
-        main_shutdown_sender: Sender<()>,
-        queue_size: Arc<AtomicUsize>,
-        is_running: Arc<AtomicBool>,
-        start_time: std::time::Instant,
-    ) -> Result<()> {
-        // Read message length
-        let mut length_bytes = [0u8; 4];
-        stream.read_exact(&mut length_bytes).await
-            .context("Failed to read message length")?;
-
-        let message_length = u32::from_le_bytes(length_bytes) as usize;
-
-        // Validate message length
-        if message_length > 1024 * 1024 { // 1MB max message
-            return Err(anyhow::anyhow!("Message too large: {} bytes", message_length));
-        }
-
-        // Read message payload
-        let mut message_buffer = vec![0u8; message_length];
-        stream.read_exact(&mut message_buffer).await
-            .context("Failed to read message payload")?;
-
-        // Deserialize message
-        let (message, _): (DaemonMessage, usize) = bincode::serde::decode_from_slice(&message_buffer, bincode::config::standard())
-            .context("Failed to deserialize message")?;
+    ///
+    /// Remote clients must present `token` in the handshake and may only
+    /// submit tasks and ping.
+    async fn handle_client<S: Stream>(&self, stream: S, token: Option<&str>) -> Result<()> {
+        let remote = token.is_some();
+        let capabilities = if remote { REMOTE_CAPABILITIES } else { CAPABILITIES };
+        let mut connection = Connection::accept(stream, token, capabilities).await?;
+        let message: DaemonMessage = match connection.recv().await {
+            Ok(Some(message)) => message,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                // Tell the client why, e.g. a typo in a JSON message sent by hand
+                let _ = connection.send(&DaemonResponse::Error(format!("{e:#}"))).await;
+                return Err(e);
+            }
+        };
 
         debug!("Received IPC message: {:?}", message);
+        if !connection.supports(message.capability()) {
+            let error = format!("'{}' is not allowed on this connection", message.capability());
+            connection.send(&DaemonResponse::Error(error.clone())).await?;
+            return Err(anyhow::anyhow!(error));
+        }
 
         // Process message and generate response
         let response = match message {
+            DaemonMessage::Subscribe(filter) => return self.stream_events(connection, filter).await,
+            // Only acknowledge tasks once they are safely on disk
             DaemonMessage::Submit(task) => {
-                // Increment queue size when task is queued
-                queue_size.fetch_add(1, Ordering::Relaxed);
-                
-                match task_sender.send_async(*task).await {
-                    Ok(()) => DaemonResponse::Ok,
-                    Err(e) => {
-                        // Decrement on failure
-                        queue_size.fetch_sub(1, Ordering::Relaxed);
-                        DaemonResponse::Error(format!("Failed to queue task: {e}"))
+                let spooled = if remote {
+                    self.spools.pending.persist_remote(*task)
+                } else {
+                    self.spools.pending.persist(*task)
+                };
+                match spooled {
+                    Ok(spooled) => {
+                        self.daemon.events().publish(&spooled, EventKind::Received);
+                        self.daemon.queue().push(spooled);
+                        DaemonResponse::Ok
                     }
+                    Err(e) => DaemonResponse::Error(format!("Failed to spool task: {e:#}")),
                 }
             }
             DaemonMessage::Status => {
-                let uptime_secs = start_time.elapsed().as_secs();
-                let current_queue_size = queue_size.load(Ordering::Relaxed);
-                let running = is_running.load(Ordering::Relaxed);
-
-                DaemonResponse::Status {
-                    queue_size: current_queue_size,
-                    is_running: running,
-                    uptime_secs,
-                }
+                let mut status = self.daemon.status().await;
+                status.uptime_secs = self.start_time.elapsed().as_secs();
+                status.is_running = self.is_running.load(Ordering::Relaxed);
+                DaemonResponse::Status(Box::new(status))
             }
             DaemonMessage::Shutdown => {
                 info!("Received shutdown request via IPC");
-                if let Err(e) = shutdown_sender.send_async(()).await {
+                if let Err(e) = self.shutdown_sender.send_async(()).await {
                     warn!("Failed to send shutdown signal to notification daemon: {}", e);
                 }
-                if let Err(e) = main_shutdown_sender.send_async(()).await {
+                if let Err(e) = self.main_shutdown_sender.send_async(()).await {
                     warn!("Failed to send shutdown signal to main process: {}", e);
                 }
                 DaemonResponse::Ok
             }
             DaemonMessage::Reload => {
-                // For now, just acknowledge reload
                 info!("Received reload request via IPC");
-                DaemonResponse::Ok
+                match (self.reload)() {
+                    Ok(()) => DaemonResponse::Ok,
+                    Err(e) => {
+                        warn!("Keeping the previous configuration: {:#}", e);
+                        DaemonResponse::Error(format!("{e:#}"))
+                    }
+                }
             }
             DaemonMessage::Ping => {
                 DaemonResponse::Ok
             }
+            DaemonMessage::DeadLetters => match self.spools.dead_letters.pending() {
+                Ok(dead_letters) => DaemonResponse::DeadLetters(dead_letters),
+                Err(e) => DaemonResponse::Error(format!("{e:#}")),
+            },
+            DaemonMessage::RetryDeadLetters(id) => self.handle_dead_letters(id, true).await,
+            DaemonMessage::PurgeDeadLetters(id) => self.handle_dead_letters(id, false).await,
         };
 
-        // Serialize and send response
-        let response_data = bincode::serde::encode_to_vec(&response, bincode::config::standard())
-            .context("Failed to serialize response")?;
+        connection.send(&response).await?;
+        debug!("Sent IPC response: {:?}", response);
+        Ok(())
+    }
+
+    /// Send matching events to a subscriber until it disconnects
+    async fn stream_events<S: Stream>(&self, mut connection: Connection<S>, filter: EventFilter) -> Result<()> {
+        let mut events = self.daemon.events().subscribe();
+        connection.send(&DaemonResponse::Ok).await?;
+        debug!("IPC client subscribed to events: {:?}", filter);
+
+        // Subscribers send nothing more, so input only arrives when they disconnect
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) if filter.matches(&event) => {
+                        if connection.send(&DaemonResponse::Event(Box::new(event))).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(missed)) => warn!("Event subscriber fell behind, skipped {} event(s)", missed),
+                    Err(RecvError::Closed) => break,
+                },
+                _ = connection.closed() => break,
+            }
+        }
 
-        let response_length = response_data.len() as u32;
-        let response_length_bytes = response_length.to_le_bytes();
+        debug!("IPC event subscriber disconnected");
+        Ok(())
+    }
 
-        // Send response length
-        stream.write_all(&response_length_bytes).await
-            .context("Failed to write response length")?;
+    /// Retry or purge one dead letter, or all of them when `id` is `None`
+    async fn handle_dead_letters(&self, id: Option<String>, retry: bool) -> DaemonResponse {
+        let dead_letters = match self.spools.dead_letters.pending() {
+            Ok(dead_letters) => dead_letters,
+            Err(e) => return DaemonResponse::Error(format!("{e:#}")),
+        };
+        let selected: Vec<SpooledTask> = dead_letters
+            .into_iter()
+            .filter(|dead_letter| id.as_ref().is_none_or(|id| dead_letter.id == *id))
+            .collect();
+        if let (Some(id), true) = (&id, selected.is_empty()) {
+            return DaemonResponse::Error(format!("No dead letter with id {id}"));
+        }
 
-        // Send response payload
-        stream.write_all(&response_data).await
-            .context("Failed to write response payload")?;
+        let mut count = 0;
+        for mut dead_letter in selected {
+            if retry {
+                // Back into the spool first, so a crash cannot lose it
+                dead_letter.task.retry_count = 0;
+                if let Err(e) = self.spools.pending.update(&dead_letter) {
+                    return DaemonResponse::Error(format!("{e:#}"));
+                }
+            }
+            if let Err(e) = self.spools.dead_letters.complete(&dead_letter.id) {
+                return DaemonResponse::Error(format!("{e:#}"));
+            }
+            if retry {
+                self.daemon.queue().push(dead_letter);
+            }
+            count += 1;
+        }
 
-        stream.flush().await
-            .context("Failed to flush response")?;
+        info!("{} {} dead letter(s)", if retry { "Retried" } else { "Purged" }, count);
+        DaemonResponse::DeadLetterCount(count)
+    }
+}
 
-        debug!("Sent IPC response: {:?}", response);
-        Ok(())
+/// Accept the next remote client, or wait forever without a TCP listener
+async fn accept_remote(
+    remote: Option<&RemoteListener>,
+) -> Result<(TcpStream, std::net::SocketAddr, Option<TlsAcceptor>, Arc<str>)> {
+    match remote {
+        Some(remote) => {
+            let (stream, peer) = remote.accept().await?;
+            Ok((stream, peer, remote.tls(), remote.token()))
+        }
+        None => std::future::pending().await,
     }
-}
\ No newline at end of file
+}
@@ -6,10 +6,19 @@
 //! - Background daemon server
 //! - Client interface for CLI communication
 
+pub mod events;
+pub mod instances;
 pub mod ipc;
 pub mod ipc_server;
+pub mod protocol;
+pub mod remote;
+pub mod queue;
+pub mod retry;
 pub mod server;
 pub mod shared;
+pub mod spool;
+pub mod stats;
+pub mod watcher;
 
 // Re-export commonly used types
 pub use shared::{DaemonMessage, DaemonResponse, NotificationTask, NtfyTaskConfig};
@@ -0,0 +1,386 @@
+//! Wire protocol between `claude-ntfy` and the daemon
+//!
+//! Every connection opens with a one-line handshake, plain enough to type
+//! into `socat`:
+//!
+//! ```text
+//! client: CNTF/1 json
+//! daemon: CNTF/1 ok submit ping status shutdown reload dead-letters subscribe
+//! ```
+//!
+//! The client names the protocol version and the encoding of the frames that
+//! follow, and over TCP adds `token=<daemon.auth_token>`. The daemon answers with its version and the messages it supports,
+//! or with `error` and a reason before closing the connection. A client and
+//! daemon of different versions thereby fail with an error that says so,
+//! rather than with a message that cannot be decoded. TCP clients are only
+//! offered `submit` and `ping`, and a client that does not finish the
+//! handshake within [`HANDSHAKE_TIMEOUT`] is disconnected.
+//!
+//! After the handshake, `bincode` frames are a little-endian `u32` length
+//! followed by the payload and `json` frames are one JSON value per line.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// First bytes of every connection
+pub const MAGIC: &str = "CNTF";
+
+/// Bumped whenever a message or response changes incompatibly
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Messages this daemon supports, as named by [`DaemonMessage::capability`]
+///
+/// [`DaemonMessage::capability`]: super::DaemonMessage::capability
+pub const CAPABILITIES: &[&str] = &["submit", "ping", "status", "shutdown", "reload", "dead-letters", "subscribe"];
+
+/// The subset of [`CAPABILITIES`] offered to clients connecting over TCP
+pub const REMOTE_CAPABILITIES: &[&str] = &["submit", "ping"];
+
+/// How long a client may take to send its handshake
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest frame the daemon accepts
+pub const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+/// Longest handshake line either side reads
+const MAX_HANDSHAKE_LEN: u64 = 1024;
+
+/// Tells users how to get a daemon matching their `claude-ntfy`
+const RESTART_HINT: &str = "restart it with 'claude-ntfy daemon stop' and 'claude-ntfy daemon start'";
+
+/// How frames are encoded after the handshake
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Compact, used by `claude-ntfy` itself
+    #[default]
+    Bincode,
+    /// One JSON value per line, for debugging by hand
+    Json,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Bincode => "bincode",
+            Encoding::Json => "json",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bincode" => Some(Encoding::Bincode),
+            "json" => Some(Encoding::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A connection that has completed the handshake
+pub struct Connection<S> {
+    stream: BufReader<S>,
+    encoding: Encoding,
+    max_frame_size: usize,
+    /// What the daemon supports, or on the daemon side what the client may ask for
+    capabilities: Vec<String>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    /// Open the client side of a connection, authenticating with `token` if given
+    pub async fn connect(stream: S, encoding: Encoding, max_frame_size: usize, token: Option<&str>) -> Result<Self> {
+        let mut stream = BufReader::new(stream);
+        let mut hello = format!("{MAGIC}/{PROTOCOL_VERSION} {}", encoding.name());
+        if let Some(token) = token {
+            hello.push_str(&format!(" token={token}"));
+        }
+        write_line(&mut stream, &hello).await?;
+
+        // Older daemons reset the connection on the unexpected handshake
+        let reply = read_line(&mut stream).await.ok().flatten().with_context(|| {
+            format!("The daemon did not answer the protocol handshake, so it is probably an older version; {RESTART_HINT}")
+        })?;
+        let (version, rest) = parse_greeting(&reply).with_context(|| {
+            format!("The daemon sent an unexpected handshake reply {reply:?}; {RESTART_HINT}")
+        })?;
+        if version != PROTOCOL_VERSION {
+            return Err(anyhow::anyhow!(
+                "The daemon speaks protocol version {version} but this claude-ntfy speaks version {PROTOCOL_VERSION}; {RESTART_HINT}"
+            ));
+        }
+
+        let capabilities = match rest.split_once(' ').unwrap_or((rest, "")) {
+            ("ok", capabilities) => capabilities.split_whitespace().map(str::to_string).collect(),
+            ("error", reason) => return Err(anyhow::anyhow!("The daemon refused the connection: {reason}")),
+            _ => return Err(anyhow::anyhow!("The daemon sent an unexpected handshake reply {reply:?}")),
+        };
+
+        Ok(Self {
+            stream,
+            encoding,
+            max_frame_size,
+            capabilities,
+        })
+    }
+
+    /// Accept the daemon side of a connection, answering the client's handshake
+    ///
+    /// With a `token`, clients that do not present it are refused. The client
+    /// is offered `capabilities`, which [`supports`](Self::supports) checks
+    /// its messages against afterwards.
+    pub async fn accept(stream: S, token: Option<&str>, capabilities: &[&str]) -> Result<Self> {
+        Self::accept_within(stream, token, capabilities, HANDSHAKE_TIMEOUT).await
+    }
+
+    async fn accept_within(stream: S, token: Option<&str>, capabilities: &[&str], timeout: Duration) -> Result<Self> {
+        let mut stream = BufReader::new(stream);
+
+        // A client that never finishes the handshake must not hold the connection
+        let hello = tokio::time::timeout(timeout, async {
+            // Clients from before the handshake start with a binary length instead
+            let mut magic = [0u8; MAGIC.len()];
+            stream.read_exact(&mut magic).await.context("Failed to read protocol handshake")?;
+            if magic != MAGIC.as_bytes() {
+                return Err(anyhow::anyhow!("Client does not speak the claude-ntfy protocol, it may be an older version"));
+            }
+            read_line(&mut stream)
+                .await?
+                .context("Client closed the connection during the handshake")
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("Client did not complete the handshake within {}s", timeout.as_secs()))??;
+
+        let greeting = format!("{MAGIC}{hello}");
+        let requested = parse_greeting(&greeting).and_then(|(version, rest)| {
+            let mut words = rest.split_whitespace();
+            let encoding = Encoding::parse(words.next()?)?;
+            let presented = words.find_map(|word| word.strip_prefix("token="));
+            Some((version, encoding, presented))
+        });
+        let refusal = match requested {
+            Some((PROTOCOL_VERSION, encoding, presented)) => match (token, presented) {
+                (Some(expected), Some(presented)) if tokens_match(expected, presented) => Ok(encoding),
+                (Some(_), _) => Err("authentication failed".to_string()),
+                (None, _) => Ok(encoding),
+            },
+            Some((version, ..)) => Err(format!("unsupported protocol version {version}, the daemon speaks {PROTOCOL_VERSION}")),
+            // Not echoed back, it may contain a token
+            None => Err("malformed handshake".to_string()),
+        };
+        let encoding = match refusal {
+            Ok(encoding) => encoding,
+            Err(reason) => {
+                write_line(&mut stream, &format!("{MAGIC}/{PROTOCOL_VERSION} error {reason}")).await?;
+                return Err(anyhow::anyhow!("Rejected client: {reason}"));
+            }
+        };
+
+        write_line(&mut stream, &format!("{MAGIC}/{PROTOCOL_VERSION} ok {}", capabilities.join(" "))).await?;
+        Ok(Self {
+            stream,
+            encoding,
+            max_frame_size: MAX_FRAME_SIZE,
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+        })
+    }
+
+    /// Whether `capability` was announced during the handshake
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Send one frame
+    pub async fn send<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        match self.encoding {
+            Encoding::Bincode => {
+                let data = bincode::serde::encode_to_vec(value, bincode::config::standard())
+                    .context("Failed to serialize frame")?;
+                let length = data.len() as u32;
+                self.stream.write_all(&length.to_le_bytes()).await
+                    .context("Failed to write frame length")?;
+                self.stream.write_all(&data).await
+                    .context("Failed to write frame payload")?;
+            }
+            Encoding::Json => {
+                let mut data = serde_json::to_vec(value).context("Failed to serialize frame")?;
+                data.push(b'\n');
+                self.stream.write_all(&data).await
+                    .context("Failed to write frame")?;
+            }
+        }
+        self.stream.flush().await.context("Failed to flush frame")
+    }
+
+    /// Receive one frame; `None` if the connection was closed before it
+    pub async fn recv<T: DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        match self.encoding {
+            Encoding::Bincode => {
+                let mut length_bytes = [0u8; 4];
+                match self.stream.read_exact(&mut length_bytes).await {
+                    Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                    result => result.context("Failed to read frame length")?,
+                };
+
+                let length = u32::from_le_bytes(length_bytes) as usize;
+                if length > self.max_frame_size {
+                    return Err(anyhow::anyhow!(
+                        "Frame too large: {} bytes (max: {})",
+                        length,
+                        self.max_frame_size
+                    ));
+                }
+
+                let mut data = vec![0u8; length];
+                self.stream.read_exact(&mut data).await
+                    .context("Failed to read frame payload")?;
+                let (value, _) = bincode::serde::decode_from_slice(&data, bincode::config::standard())
+                    .with_context(|| format!("Failed to decode frame of protocol version {PROTOCOL_VERSION}"))?;
+                Ok(Some(value))
+            }
+            Encoding::Json => loop {
+                let mut line = Vec::new();
+                let limit = self.max_frame_size as u64 + 1;
+                if (&mut self.stream).take(limit).read_until(b'\n', &mut line).await
+                    .context("Failed to read frame")? == 0
+                {
+                    return Ok(None);
+                }
+                if line.len() > self.max_frame_size {
+                    return Err(anyhow::anyhow!("Frame too large (max: {} bytes)", self.max_frame_size));
+                }
+                // Skip blank lines typed between messages
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                return serde_json::from_slice(&line).map(Some).context("Failed to decode JSON frame");
+            },
+        }
+    }
+
+    /// Wait until the other side closes the connection or sends more data
+    pub async fn closed(&mut self) {
+        let _ = self.stream.fill_buf().await;
+    }
+}
+
+/// Compare tokens in constant time, so response times do not reveal a prefix
+fn tokens_match(expected: &str, presented: &str) -> bool {
+    expected.len() == presented.len()
+        && expected.bytes().zip(presented.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Split `CNTF/<version> <rest>` into the version and the rest
+fn parse_greeting(line: &str) -> Option<(u32, &str)> {
+    let (version, rest) = line.strip_prefix(MAGIC)?.strip_prefix('/')?.split_once(' ')?;
+    Some((version.parse().ok()?, rest.trim()))
+}
+
+async fn write_line<S: AsyncWrite + Unpin>(stream: &mut S, line: &str) -> Result<()> {
+    stream.write_all(format!("{line}\n").as_bytes()).await
+        .context("Failed to write protocol handshake")?;
+    stream.flush().await.context("Failed to flush protocol handshake")
+}
+
+/// Read a handshake line without its line ending; `None` at end of stream
+async fn read_line<S: AsyncBufReadExt + Unpin>(stream: &mut S) -> Result<Option<String>> {
+    let mut line = Vec::new();
+    (&mut *stream).take(MAX_HANDSHAKE_LEN).read_until(b'\n', &mut line).await
+        .context("Failed to read protocol handshake")?;
+    if line.last() != Some(&b'\n') {
+        return Ok(None);
+    }
+    let line = String::from_utf8(line).context("Protocol handshake is not UTF-8")?;
+    Ok(Some(line.trim_end().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::{DaemonMessage, DaemonResponse};
+    use tokio::io::duplex;
+
+    async fn round_trip(encoding: Encoding) {
+        let (client, daemon) = duplex(4096);
+        let daemon = tokio::spawn(async move {
+            let mut connection = Connection::accept(daemon, None, CAPABILITIES).await.unwrap();
+            let message: DaemonMessage = connection.recv().await.unwrap().unwrap();
+            assert!(matches!(message, DaemonMessage::Reload));
+            connection.send(&DaemonResponse::Error("bad config".to_string())).await.unwrap();
+            assert!(connection.recv::<DaemonMessage>().await.unwrap().is_none());
+        });
+
+        let mut connection = Connection::connect(client, encoding, MAX_FRAME_SIZE, None).await.unwrap();
+        assert!(connection.supports("subscribe"));
+        assert!(!connection.supports("teleport"));
+        connection.send(&DaemonMessage::Reload).await.unwrap();
+        let response: DaemonResponse = connection.recv().await.unwrap().unwrap();
+        assert!(matches!(response, DaemonResponse::Error(e) if e == "bad config"));
+        drop(connection);
+        daemon.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_in_both_encodings() {
+        round_trip(Encoding::Bincode).await;
+        round_trip(Encoding::Json).await;
+    }
+
+    #[tokio::test]
+    async fn test_version_mismatch_is_explained() {
+        // A daemon from a future version
+        let (client, mut daemon) = duplex(4096);
+        tokio::spawn(async move {
+            let mut hello = [0u8; 64];
+            let _ = daemon.read(&mut hello).await;
+            daemon.write_all(b"CNTF/2 ok submit\n").await.unwrap();
+        });
+        let error = Connection::connect(client, Encoding::Bincode, MAX_FRAME_SIZE, None).await.err().unwrap();
+        assert!(error.to_string().contains("protocol version 2"), "{error}");
+        assert!(error.to_string().contains("claude-ntfy daemon start"), "{error}");
+
+        // A daemon from before the handshake drops the connection
+        let (client, mut daemon) = duplex(4096);
+        tokio::spawn(async move {
+            let mut length = [0u8; 4];
+            let _ = daemon.read_exact(&mut length).await;
+        });
+        let error = Connection::connect(client, Encoding::Bincode, MAX_FRAME_SIZE, None).await.err().unwrap();
+        assert!(error.to_string().contains("older version"), "{error}");
+
+        // A client from a future version
+        let (mut client, daemon) = duplex(4096);
+        let accepted = tokio::spawn(Connection::accept(daemon, None, CAPABILITIES));
+        client.write_all(b"CNTF/2 bincode\n").await.unwrap();
+        let mut reply = String::new();
+        BufReader::new(client).read_line(&mut reply).await.unwrap();
+        assert_eq!(reply, "CNTF/1 error unsupported protocol version 2, the daemon speaks 1\n");
+        assert!(accepted.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_is_required_when_configured() {
+        for (presented, accepted) in [(Some("s3cret"), true), (Some("s3cre7"), false), (None, false)] {
+            let (client, daemon) = duplex(4096);
+            let daemon = tokio::spawn(async move {
+                Connection::accept(daemon, Some("s3cret"), REMOTE_CAPABILITIES).await.is_ok()
+            });
+            let connected = Connection::connect(client, Encoding::Bincode, MAX_FRAME_SIZE, presented).await;
+            assert_eq!(daemon.await.unwrap(), accepted);
+            match connected {
+                Ok(connection) => assert!(accepted && connection.supports("submit") && !connection.supports("shutdown")),
+                Err(e) => assert!(!accepted && e.to_string().contains("authentication failed"), "{e}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_silent_client_is_dropped() {
+        let (_client, daemon) = duplex(4096);
+        let error = Connection::accept_within(daemon, Some("s3cret"), REMOTE_CAPABILITIES, Duration::from_millis(50))
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("did not complete the handshake"), "{error}");
+    }
+}
@@ -0,0 +1,302 @@
+//! Priority queue of tasks waiting for delivery
+//!
+//! Tasks are delivered highest priority first, so a permission prompt is not
+//! stuck behind a burst of tool notifications. Waiting tasks age: every
+//! `queue_aging_secs` of waiting counts as one priority level, so low
+//! priority tasks are still delivered under sustained load.
+//!
+//! Because every task ages at the same rate, aging never changes the
+//! relative order of queued tasks. Each task is therefore keyed once on
+//! arrival by its arrival time minus one aging interval per priority level,
+//! as if higher priority tasks had arrived earlier.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::warn;
+
+use super::events::EventBus;
+use super::shared::{defaults, EventKind};
+use super::spool::{DeliveryAttempt, SpooledTask, Spools};
+use crate::hooks::filter::is_decision_hook;
+use crate::shared::config::{Config, DropPolicy};
+
+/// Ordering key: lower keys are delivered first, ties by arrival
+type QueueKey = (i64, u64);
+
+/// Queue limits and ordering, replaced as a whole on reload
+struct QueueSettings {
+    max_size: usize,
+    aging: Duration,
+    decision_hook_priority: u8,
+    drop_policy: DropPolicy,
+}
+
+impl From<&Config> for QueueSettings {
+    fn from(config: &Config) -> Self {
+        Self {
+            max_size: config.daemon.max_queue_size.max(1),
+            aging: Duration::from_secs(config.daemon.queue_aging_secs.max(1)),
+            decision_hook_priority: config.hooks.decision_hook_priority,
+            drop_policy: config.daemon.drop_policy,
+        }
+    }
+}
+
+/// Bounded priority queue shared by the IPC server and the daemon
+pub struct TaskQueue {
+    tasks: Mutex<BTreeMap<QueueKey, SpooledTask>>,
+    next_sequence: AtomicU64,
+    available: Notify,
+    epoch: Instant,
+    settings: RwLock<QueueSettings>,
+    spools: Arc<Spools>,
+    events: Arc<EventBus>,
+}
+
+impl TaskQueue {
+    /// Create a queue using the daemon settings from `config`
+    ///
+    /// Tasks shed from a full queue are moved to the dead-letter queue.
+    pub fn new(config: &Config, spools: Arc<Spools>, events: Arc<EventBus>) -> Self {
+        Self {
+            tasks: Mutex::new(BTreeMap::new()),
+            next_sequence: AtomicU64::new(0),
+            available: Notify::new(),
+            epoch: Instant::now(),
+            settings: RwLock::new(QueueSettings::from(config)),
+            spools,
+            events,
+        }
+    }
+
+    /// Apply new queue settings from a reloaded configuration
+    ///
+    /// Queued tasks keep their place; new priorities and aging apply to
+    /// tasks arriving from now on, and a smaller limit on the next push.
+    pub fn reconfigure(&self, config: &Config) {
+        *self.settings.write().unwrap() = QueueSettings::from(config);
+    }
+
+    /// Queue a task, shedding tasks according to the drop policy when full
+    pub fn push(&self, task: SpooledTask) {
+        let dropped = {
+            let mut tasks = self.tasks.lock().unwrap();
+            tasks.insert(self.key(&task), task);
+
+            let mut dropped = Vec::new();
+            while tasks.len() > self.max_size() {
+                match self.victim(&tasks).and_then(|victim| tasks.remove(&victim)) {
+                    Some(victim) => dropped.push(victim),
+                    None => break,
+                }
+            }
+            dropped
+        };
+        self.available.notify_one();
+
+        for dropped in dropped {
+            self.shed(dropped);
+        }
+    }
+
+    /// Wait for the next task to deliver
+    pub async fn pop(&self) -> SpooledTask {
+        loop {
+            if let Some(task) = self.try_pop() {
+                return task;
+            }
+            self.available.notified().await;
+        }
+    }
+
+    /// Take the next task to deliver, if any
+    pub fn try_pop(&self) -> Option<SpooledTask> {
+        self.tasks.lock().unwrap().pop_first().map(|(_, task)| task)
+    }
+
+    /// Number of queued tasks
+    pub fn len(&self) -> usize {
+        self.tasks.lock().unwrap().len()
+    }
+
+    /// Whether no task is queued
+    pub fn is_empty(&self) -> bool {
+        self.tasks.lock().unwrap().is_empty()
+    }
+
+    fn max_size(&self) -> usize {
+        self.settings.read().unwrap().max_size
+    }
+
+    /// Priority used for ordering; decision hooks are raised to `decision_hook_priority`
+    fn priority(&self, task: &SpooledTask) -> u8 {
+        let priority = task.task.ntfy_config.priority.unwrap_or(defaults::PRIORITY);
+        if is_decision_hook(&task.task.hook_name) {
+            priority.max(self.settings.read().unwrap().decision_hook_priority)
+        } else {
+            priority
+        }
+    }
+
+    fn key(&self, task: &SpooledTask) -> QueueKey {
+        (
+            self.rank(task, self.epoch.elapsed()),
+            self.next_sequence.fetch_add(1, Ordering::Relaxed),
+        )
+    }
+
+    /// Rank of a task arriving at `arrival` after the epoch; lower ranks go first
+    fn rank(&self, task: &SpooledTask, arrival: Duration) -> i64 {
+        let aging = self.settings.read().unwrap().aging;
+        let boost = i64::from(self.priority(task)) * aging.as_millis() as i64;
+        arrival.as_millis() as i64 - boost
+    }
+
+    /// Pick the task to shed from a full queue, never a decision hook
+    fn victim(&self, tasks: &BTreeMap<QueueKey, SpooledTask>) -> Option<QueueKey> {
+        let mut candidates = tasks
+            .iter()
+            .filter(|(_, task)| !is_decision_hook(&task.task.hook_name))
+            .map(|(key, _)| *key);
+
+        match self.settings.read().unwrap().drop_policy {
+            DropPolicy::LowestPriority => candidates.next_back(),
+            DropPolicy::Oldest => candidates.min_by_key(|(_, sequence)| *sequence),
+            DropPolicy::Newest => candidates.max_by_key(|(_, sequence)| *sequence),
+        }
+    }
+
+    fn shed(&self, mut dropped: SpooledTask) {
+        warn!(
+            "Notification queue is full ({} tasks), moving a {} task to the dead-letter queue",
+            self.max_size(), dropped.task.hook_name
+        );
+        let error = "Dropped because the daemon queue was full".to_string();
+        dropped.attempts.push(DeliveryAttempt {
+            at: chrono::Local::now(),
+            backends: Vec::new(),
+            error: error.clone(),
+        });
+        if let Err(e) = self.spools.bury(&dropped) {
+            warn!("{:#}", e);
+        }
+        self.events.publish(&dropped, EventKind::Failed { error });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::{NotificationTask, NtfyTaskConfig};
+    use tempfile::TempDir;
+
+    fn task(hook_name: &str, priority: u8) -> SpooledTask {
+        let ntfy_config = NtfyTaskConfig {
+            priority: Some(priority),
+            ..Default::default()
+        };
+        SpooledTask {
+            id: format!("{hook_name}-{priority}"),
+            task: NotificationTask {
+                hook_name: hook_name.to_string(),
+                hook_data: "{}".to_string(),
+                retry_count: 0,
+                timestamp: chrono::Local::now(),
+                ntfy_config,
+                project_path: None,
+                backends: Vec::new(),
+            },
+            attempts: Vec::new(),
+            remote: false,
+        }
+    }
+
+    fn queue(temp_dir: &TempDir, max_size: usize, drop_policy: DropPolicy) -> TaskQueue {
+        let mut config = Config::default();
+        config.daemon.max_queue_size = max_size;
+        config.daemon.drop_policy = drop_policy;
+        config.hooks.decision_hook_priority = 5;
+        let spools = Spools::open_for_socket(&temp_dir.path().join("daemon.sock")).unwrap();
+        TaskQueue::new(&config, Arc::new(spools), Arc::new(EventBus::default()))
+    }
+
+    fn drain(queue: &TaskQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.try_pop()).map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_decision_hooks_jump_ahead() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = queue(&temp_dir, 10, DropPolicy::LowestPriority);
+        queue.push(task("PostToolUse", 3));
+        queue.push(task("Stop", 4));
+        queue.push(task("Notification", 2));
+        queue.push(task("PreToolUse", 3));
+
+        assert_eq!(drain(&queue), vec!["Notification-2", "Stop-4", "PostToolUse-3", "PreToolUse-3"]);
+    }
+
+    #[test]
+    fn test_waiting_tasks_age() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = queue(&temp_dir, 10, DropPolicy::LowestPriority);
+        let low = task("PostToolUse", 1);
+        let high = task("Stop", 3);
+
+        // Two aging intervals make up for the two levels between them
+        let rank_after = |secs| queue.rank(&high, Duration::from_secs(secs));
+        assert!(queue.rank(&low, Duration::ZERO) > rank_after(59));
+        assert!(queue.rank(&low, Duration::ZERO) < rank_after(61));
+    }
+
+    #[test]
+    fn test_full_queue_sheds_by_policy() {
+        let temp_dir = TempDir::new().unwrap();
+        let lowest = queue(&temp_dir, 2, DropPolicy::LowestPriority);
+        lowest.push(task("PostToolUse", 2));
+        lowest.push(task("Notification", 1));
+        lowest.push(task("Stop", 3));
+        assert_eq!(drain(&lowest), vec!["Notification-1", "Stop-3"]);
+
+        let oldest = queue(&temp_dir, 2, DropPolicy::Oldest);
+        oldest.push(task("Notification", 3));
+        oldest.push(task("PostToolUse", 3));
+        oldest.push(task("Stop", 3));
+        assert_eq!(drain(&oldest), vec!["Notification-3", "Stop-3"]);
+
+        let newest = queue(&temp_dir, 1, DropPolicy::Newest);
+        newest.push(task("Stop", 3));
+        newest.push(task("PostToolUse", 5));
+        assert_eq!(drain(&newest), vec!["Stop-3"]);
+
+        let dead_letters = Spools::open_for_socket(&temp_dir.path().join("daemon.sock"))
+            .unwrap()
+            .dead_letters
+            .pending()
+            .unwrap();
+        let mut shed: Vec<String> = dead_letters.into_iter().map(|t| t.id).collect();
+        shed.sort();
+        assert_eq!(shed, vec!["PostToolUse-2", "PostToolUse-3", "PostToolUse-5"]);
+    }
+
+    #[test]
+    fn test_reconfigure_shrinks_on_next_push() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = queue(&temp_dir, 10, DropPolicy::LowestPriority);
+        queue.push(task("PostToolUse", 1));
+        queue.push(task("PostToolUse", 2));
+        queue.push(task("Stop", 3));
+
+        let mut config = Config::default();
+        config.daemon.max_queue_size = 2;
+        config.daemon.drop_policy = DropPolicy::Oldest;
+        queue.reconfigure(&config);
+        assert_eq!(queue.len(), 3);
+
+        queue.push(task("Stop", 4));
+        assert_eq!(drain(&queue), vec!["Stop-4", "Stop-3"]);
+    }
+}
@@ -0,0 +1,240 @@
+//! TCP access to the daemon from devcontainers and remote hosts
+//!
+//! With `daemon.listen_address` the daemon accepts clients over TCP as well as
+//! on its Unix socket, wrapped in TLS when `tls_cert` and `tls_key` are set.
+//! TCP clients must present `daemon.auth_token` in the protocol handshake and
+//! may then only submit tasks and ping. Their tasks are delivered with the
+//! backends of the daemon's own configuration, so a client can neither name a
+//! project's backends nor send backend definitions of its own.
+//! Hooks running elsewhere set `daemon.remote_address`, e.g.
+//! `tls://laptop.local:7878`, and the same token to deliver through it.
+
+use anyhow::{Context, Result};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore, ServerConfig};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::info;
+
+use super::protocol::HANDSHAKE_TIMEOUT;
+use crate::shared::config::DaemonConfig;
+
+/// A connection to or from the daemon, over any transport
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Stream for S {}
+
+/// The daemon's TCP listener for remote clients
+pub struct RemoteListener {
+    listener: TcpListener,
+    tls: Option<TlsAcceptor>,
+    token: Arc<str>,
+}
+
+impl RemoteListener {
+    /// Bind the listener configured by `daemon.listen_address`, if any
+    pub async fn bind(config: &DaemonConfig) -> Result<Option<Self>> {
+        let Some(address) = &config.listen_address else {
+            return Ok(None);
+        };
+        let token = config
+            .auth_token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+            .context("daemon.listen_address requires daemon.auth_token")?;
+        let tls = match (&config.tls_cert, &config.tls_key) {
+            (Some(cert), Some(key)) => Some(tls_acceptor(cert, key)?),
+            (None, None) => None,
+            _ => return Err(anyhow::anyhow!("daemon.tls_cert and daemon.tls_key must be set together")),
+        };
+
+        let listener = TcpListener::bind(address)
+            .await
+            .with_context(|| format!("Failed to listen on {address}"))?;
+        info!(
+            "Accepting remote clients on {} ({})",
+            listener.local_addr()?,
+            if tls.is_some() { "TLS" } else { "plain TCP" }
+        );
+
+        Ok(Some(Self {
+            listener,
+            tls,
+            token: Arc::from(token),
+        }))
+    }
+
+    /// Wait for the next client
+    ///
+    /// The TLS handshake is left to [`secure`], so that a slow client does
+    /// not hold up the others.
+    pub async fn accept(&self) -> Result<(TcpStream, SocketAddr)> {
+        let (stream, peer) = self.listener.accept().await.context("Failed to accept TCP connection")?;
+        let _ = stream.set_nodelay(true);
+        Ok((stream, peer))
+    }
+
+    /// The TLS settings of accepted connections, if TLS is enabled
+    pub fn tls(&self) -> Option<TlsAcceptor> {
+        self.tls.clone()
+    }
+
+    /// The token remote clients must present
+    pub fn token(&self) -> Arc<str> {
+        self.token.clone()
+    }
+}
+
+/// Complete the TLS handshake of an accepted connection, if TLS is enabled
+pub async fn secure(stream: TcpStream, tls: Option<TlsAcceptor>) -> Result<Box<dyn Stream>> {
+    Ok(match tls {
+        Some(tls) => Box::new(
+            tokio::time::timeout(HANDSHAKE_TIMEOUT, tls.accept(stream))
+                .await
+                .context("TLS handshake timed out")?
+                .context("TLS handshake failed")?,
+        ),
+        None => Box::new(stream),
+    })
+}
+
+/// A daemon on another host, as configured by `daemon.remote_address`
+#[derive(Debug, Clone)]
+pub struct RemoteDaemon {
+    /// `host:port` to connect to
+    address: String,
+    tls: bool,
+    token: String,
+    ca_cert: Option<PathBuf>,
+}
+
+impl RemoteDaemon {
+    /// The remote daemon configured for hooks, if any
+    ///
+    /// `remote_address` is `host:port` for plain TCP or `tls://host:port`.
+    pub fn from_config(config: &DaemonConfig) -> Result<Option<Self>> {
+        let Some(remote_address) = config.remote_address.as_deref().filter(|a| !a.is_empty()) else {
+            return Ok(None);
+        };
+        let (address, tls) = match remote_address.split_once("://") {
+            Some(("tls", address)) => (address, true),
+            Some(("tcp", address)) => (address, false),
+            Some((scheme, _)) => {
+                return Err(anyhow::anyhow!("Unsupported scheme {scheme}:// in daemon.remote_address, use tcp:// or tls://"))
+            }
+            None => (remote_address, false),
+        };
+        let token = config
+            .auth_token
+            .clone()
+            .filter(|token| !token.is_empty())
+            .context("daemon.remote_address requires daemon.auth_token")?;
+
+        Ok(Some(Self {
+            address: address.to_string(),
+            tls,
+            token,
+            ca_cert: config.remote_ca_cert.clone(),
+        }))
+    }
+
+    /// Connect, completing the TLS handshake if enabled
+    pub async fn connect(&self) -> Result<Box<dyn Stream>> {
+        let stream = TcpStream::connect(&self.address)
+            .await
+            .with_context(|| format!("Failed to connect to remote daemon at {}", self.address))?;
+        let _ = stream.set_nodelay(true);
+        if !self.tls {
+            return Ok(Box::new(stream));
+        }
+
+        let host = self.address.rsplit_once(':').map_or(self.address.as_str(), |(host, _)| host);
+        let server_name = ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']').to_string())
+            .with_context(|| format!("Invalid host in daemon.remote_address: {host}"))?;
+        let stream = TlsConnector::from(Arc::new(self.tls_config()?))
+            .connect(server_name, stream)
+            .await
+            .with_context(|| format!("TLS handshake with remote daemon at {} failed", self.address))?;
+        Ok(Box::new(stream))
+    }
+
+    /// The token presented to the remote daemon
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    fn tls_config(&self) -> Result<ClientConfig> {
+        let mut roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        if let Some(ca_cert) = &self.ca_cert {
+            for cert in read_certs(ca_cert)? {
+                roots.add(cert).with_context(|| format!("Invalid CA certificate in {}", ca_cert.display()))?;
+            }
+        }
+
+        Ok(ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_no_client_auth())
+    }
+}
+
+impl std::fmt::Display for RemoteDaemon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}", if self.tls { "tls" } else { "tcp" }, self.address)
+    }
+}
+
+fn tls_acceptor(cert: &Path, key: &Path) -> Result<TlsAcceptor> {
+    let key = PrivateKeyDer::from_pem_file(key)
+        .with_context(|| format!("Failed to read TLS key {}", key.display()))?;
+    let config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(read_certs(cert)?, key)
+        .context("Invalid TLS certificate or key")?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read certificates from {}", path.display()))?;
+    if certs.is_empty() {
+        return Err(anyhow::anyhow!("No certificates found in {}", path.display()));
+    }
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_address_schemes() {
+        let mut config = crate::shared::config::Config::default().daemon;
+        assert!(RemoteDaemon::from_config(&config).unwrap().is_none());
+
+        config.remote_address = Some("host.docker.internal:7878".to_string());
+        let error = RemoteDaemon::from_config(&config).unwrap_err();
+        assert!(error.to_string().contains("auth_token"), "{error}");
+
+        config.auth_token = Some("s3cret".to_string());
+        let remote = RemoteDaemon::from_config(&config).unwrap().unwrap();
+        assert_eq!(remote.to_string(), "tcp://host.docker.internal:7878");
+
+        config.remote_address = Some("tls://laptop.local:7878".to_string());
+        let remote = RemoteDaemon::from_config(&config).unwrap().unwrap();
+        assert_eq!(remote.to_string(), "tls://laptop.local:7878");
+        assert_eq!(remote.token(), "s3cret");
+
+        config.remote_address = Some("https://laptop.local:7878".to_string());
+        assert!(RemoteDaemon::from_config(&config).is_err());
+    }
+}
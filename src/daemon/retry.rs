@@ -0,0 +1,135 @@
+//! Scheduled retries for failed notification tasks
+//!
+//! Failed tasks wait here until their due time instead of blocking the
+//! daemon, so other tasks keep flowing while a backend is down.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use tokio::time::Instant;
+
+use super::spool::SpooledTask;
+
+/// A task waiting for its next attempt
+struct ScheduledTask {
+    due: Instant,
+    /// Insertion order, so tasks due at the same time keep their order
+    sequence: u64,
+    task: SpooledTask,
+}
+
+impl ScheduledTask {
+    fn key(&self) -> (Instant, u64) {
+        (self.due, self.sequence)
+    }
+}
+
+impl PartialEq for ScheduledTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ScheduledTask {}
+
+impl PartialOrd for ScheduledTask {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledTask {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Tasks scheduled for a later attempt, ordered by due time
+#[derive(Default)]
+pub struct RetryQueue {
+    tasks: BinaryHeap<Reverse<ScheduledTask>>,
+    next_sequence: u64,
+}
+
+impl RetryQueue {
+    /// Schedule a task for another attempt at `due`
+    pub fn schedule(&mut self, task: SpooledTask, due: Instant) {
+        self.tasks.push(Reverse(ScheduledTask {
+            due,
+            sequence: self.next_sequence,
+            task,
+        }));
+        self.next_sequence += 1;
+    }
+
+    /// When the earliest scheduled task is due
+    pub fn next_due(&self) -> Option<Instant> {
+        self.tasks.peek().map(|Reverse(scheduled)| scheduled.due)
+    }
+
+    /// Remove and return every task due at or before `now`, earliest first
+    pub fn pop_due(&mut self, now: Instant) -> Vec<SpooledTask> {
+        let mut due = Vec::new();
+        while self.next_due().is_some_and(|next| next <= now) {
+            if let Some(Reverse(scheduled)) = self.tasks.pop() {
+                due.push(scheduled.task);
+            }
+        }
+        due
+    }
+
+    /// Number of tasks waiting for a retry
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Whether no task is waiting for a retry
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::{NotificationTask, NtfyTaskConfig};
+    use std::time::Duration;
+
+    fn task(hook_name: &str) -> SpooledTask {
+        let task = NotificationTask {
+            hook_name: hook_name.to_string(),
+            hook_data: "{}".to_string(),
+            retry_count: 1,
+            timestamp: chrono::Local::now(),
+            ntfy_config: NtfyTaskConfig::default(),
+            project_path: None,
+            backends: Vec::new(),
+        };
+        SpooledTask {
+            id: hook_name.to_string(),
+            task,
+            attempts: Vec::new(),
+            remote: false,
+        }
+    }
+
+    #[test]
+    fn test_pop_due_in_order() {
+        let now = Instant::now();
+        let mut queue = RetryQueue::default();
+        queue.schedule(task("late"), now + Duration::from_secs(10));
+        queue.schedule(task("first"), now + Duration::from_secs(1));
+        queue.schedule(task("second"), now + Duration::from_secs(1));
+
+        assert_eq!(queue.next_due(), Some(now + Duration::from_secs(1)));
+        assert!(queue.pop_due(now).is_empty());
+
+        let due: Vec<String> = queue
+            .pop_due(now + Duration::from_secs(5))
+            .into_iter()
+            .map(|t| t.task.hook_name)
+            .collect();
+        assert_eq!(due, vec!["first", "second"]);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.next_due(), Some(now + Duration::from_secs(10)));
+    }
+}
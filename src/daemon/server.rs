@@ -1,60 +1,206 @@
 use anyhow::{Context, Result};
 use flume::Receiver;
-use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
-use tokio::time::{sleep, Duration};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep_until, Duration, Instant};
 use tracing::{debug, error, info, warn};
 
 // Import specific items from daemon modules
-use crate::shared::templates::{MessageFormatter, TemplateEngine, TemplateStyle};
-use crate::shared::clients::{traits::NotificationClient, AsyncNtfyClient};
-use crate::ntfy::NtfyMessage;
-use super::shared::NotificationTask;
+use crate::config::ConfigManager;
+use crate::shared::templates::NotificationRenderer;
+use crate::shared::clients::traits::HookEvent;
+use crate::shared::clients::{combine_results, BackendRegistry, ClientCache};
+use crate::shared::config::DaemonConfig;
+use super::queue::TaskQueue;
+use super::retry::RetryQueue;
+use super::events::EventBus;
+use super::shared::{DaemonStatus, EventKind};
+use super::spool::{DeliveryAttempt, SpooledTask, Spools};
+use super::stats::StatsCollector;
+use super::watcher::ConfigWatcher;
 
 
 // NotificationTask is now imported from shared module
 
-pub struct NotificationDaemon {
-    template_engine: Arc<TemplateEngine>,
-    message_formatter: Arc<MessageFormatter>,
-    task_receiver: Receiver<NotificationTask>,
-    shutdown_receiver: Receiver<()>,
-    queue_size: Arc<AtomicUsize>,
+/// Settings the daemon only reads at startup that differ between two configurations
+///
+/// The listener keeps its address, token and certificate, and the watcher
+/// runs or not, until the daemon restarts.
+fn restart_only_changes(old: &DaemonConfig, new: &DaemonConfig) -> Vec<&'static str> {
+    [
+        ("workers", old.workers.max(1) != new.workers.max(1)),
+        ("socket_path", old.socket_path != new.socket_path),
+        ("log_path", old.log_path != new.log_path),
+        ("watch_config", old.watch_config != new.watch_config),
+        ("listen_address", old.listen_address != new.listen_address),
+        ("auth_token", old.auth_token != new.auth_token),
+        ("tls_cert", old.tls_cert != new.tls_cert),
+        ("tls_key", old.tls_key != new.tls_key),
+    ]
+    .into_iter()
+    .filter(|(_, changed)| *changed)
+    .map(|(setting, _)| setting)
+    .collect()
+}
+
+/// Outcome of one delivery attempt
+enum Delivery {
+    Sent { backends: Vec<String> },
+    /// Some backends failed and may succeed when retried
+    Failed { backends: Vec<String>, error: anyhow::Error },
+    /// The task cannot be delivered, e.g. because its hook data is malformed
+    Invalid(anyhow::Error),
+}
+
+/// Daemon-level retries of failed tasks
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
     max_retries: u32,
     retry_delay: Duration,
 }
 
+impl From<&DaemonConfig> for RetryPolicy {
+    fn from(config: &DaemonConfig) -> Self {
+        Self {
+            max_retries: config.retry_attempts,
+            retry_delay: Duration::from_secs(config.retry_delay_secs),
+        }
+    }
+}
+
+/// Delivers queued tasks with a pool of workers
+///
+/// Up to `workers` tasks are delivered at once. Tasks from the same Claude
+/// Code session are delivered one after another in queue order, so a
+/// session's notifications never arrive out of order.
+pub struct NotificationDaemon {
+    renderer: Arc<NotificationRenderer>,
+    queue: Arc<TaskQueue>,
+    shutdown_receiver: Receiver<()>,
+    spools: Arc<Spools>,
+    retries: Mutex<RetryQueue>,
+    /// Wakes the dispatch loop when a worker schedules a retry
+    retry_scheduled: Notify,
+    clients: RwLock<Arc<ClientCache>>,
+    /// The daemon's configuration, which task backends are resolved against
+    config: RwLock<Arc<ConfigManager>>,
+    /// Daemon settings at startup, for the ones only a restart applies
+    started_with: DaemonConfig,
+    workers: usize,
+    /// Tasks waiting for an earlier task of their session, by session id
+    sessions: std::sync::Mutex<HashMap<String, VecDeque<SpooledTask>>>,
+    retry_policy: RwLock<RetryPolicy>,
+    /// Learns the project configs to watch from the local tasks delivered,
+    /// with `daemon.watch_config`
+    watcher: Option<Arc<ConfigWatcher>>,
+    /// Loaded configurations of watched projects by config path, `None` for a
+    /// project without one; entries are dropped when the watcher sees a change
+    project_configs: std::sync::Mutex<HashMap<PathBuf, Option<Arc<ConfigManager>>>>,
+    stats: StatsCollector,
+    events: Arc<EventBus>,
+}
+
 impl NotificationDaemon {
     pub fn new(
-        task_receiver: Receiver<NotificationTask>,
+        queue: Arc<TaskQueue>,
         shutdown_receiver: Receiver<()>,
-        queue_size: Arc<AtomicUsize>,
+        spools: Arc<Spools>,
+        config: Arc<ConfigManager>,
+        watcher: Option<Arc<ConfigWatcher>>,
+        events: Arc<EventBus>,
     ) -> Result<Self> {
-        let template_engine = Arc::new(TemplateEngine::new_with_style(TemplateStyle::Compact)?);
-        let message_formatter = Arc::new(MessageFormatter::new(TemplateStyle::Compact));
+        let renderer = Arc::new(NotificationRenderer::new()?);
+        let daemon_config = &config.config().daemon;
 
         Ok(NotificationDaemon {
-            template_engine,
-            message_formatter,
-            task_receiver,
+            renderer,
+            queue,
             shutdown_receiver,
-            queue_size,
-            max_retries: 3, // Default retry attempts
-            retry_delay: Duration::from_secs(5), // Default retry delay
+            spools,
+            retries: Mutex::new(RetryQueue::default()),
+            retry_scheduled: Notify::new(),
+            clients: RwLock::new(Arc::new(ClientCache::new(daemon_config.max_per_server))),
+            workers: daemon_config.workers.max(1),
+            sessions: std::sync::Mutex::new(HashMap::new()),
+            retry_policy: RwLock::new(RetryPolicy::from(daemon_config)),
+            started_with: daemon_config.clone(),
+            config: RwLock::new(config),
+            watcher,
+            project_configs: std::sync::Mutex::new(HashMap::new()),
+            stats: StatsCollector::default(),
+            events,
         })
     }
 
-    pub async fn run(self) -> Result<()> {
-        info!("Notification daemon started");
+    /// Where task events are published for subscribers
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// The queue tasks are delivered from
+    pub fn queue(&self) -> &Arc<TaskQueue> {
+        &self.queue
+    }
+
+    /// Queue sizes and delivery statistics
+    ///
+    /// `is_running` and `uptime_secs` are left for the IPC server to fill in.
+    pub async fn status(&self) -> DaemonStatus {
+        let [projects, hooks, backends] = self.stats.report();
+        DaemonStatus {
+            queue_size: self.queue.len(),
+            retry_queue_size: self.retries.lock().await.len(),
+            workers: self.workers,
+            projects,
+            hooks,
+            backends,
+            ..Default::default()
+        }
+    }
+
+    /// Apply the backends, retry policy and backend limits of a reloaded configuration
+    ///
+    /// Clients are created afresh with the new per-server limit; deliveries
+    /// already in progress finish with the clients they started with. A
+    /// warning is logged for each changed setting that only a restart applies.
+    pub fn reconfigure(&self, config: Arc<ConfigManager>) {
+        let daemon_config = &config.config().daemon;
+        *self.retry_policy.write().unwrap() = RetryPolicy::from(daemon_config);
+        *self.clients.write().unwrap() = Arc::new(ClientCache::new(daemon_config.max_per_server));
+        for setting in restart_only_changes(&self.started_with, daemon_config) {
+            warn!("daemon.{} changed; restart the daemon to apply it", setting);
+        }
+        *self.config.write().unwrap() = config;
+    }
+
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        info!("Notification daemon started with {} worker(s)", self.workers);
+        let workers = Arc::new(Semaphore::new(self.workers));
 
         loop {
+            let next_retry = self.retries.lock().await.next_due();
+
             tokio::select! {
-                // Handle incoming notification tasks
-                task = self.receive_task() => {
-                    if let Some(task) = task {
-                        self.process_task(task).await;
-                    }
+                // Hand the most urgent queued task to a free worker
+                (permit, task) = async {
+                    let permit = workers.clone().acquire_owned().await;
+                    (permit, self.queue.pop().await)
+                } => {
+                    let permit = permit.context("Worker pool closed")?;
+                    self.dispatch(task, permit);
                 }
 
+                // Queue failed tasks again once their retry is due
+                _ = sleep_until(next_retry.unwrap_or_else(Instant::now)), if next_retry.is_some() => {
+                    self.requeue_due_retries().await;
+                }
+
+                // Pick up the due time of a retry scheduled meanwhile
+                _ = self.retry_scheduled.notified() => {}
+
                 // Handle IPC shutdown signal
                 _ = self.shutdown_receiver.recv_async() => {
                     info!("Received shutdown signal, stopping notification daemon");
@@ -63,158 +209,281 @@ impl NotificationDaemon {
             }
         }
 
-        // Process remaining tasks before shutdown
+        // Let in-flight deliveries finish, then process remaining tasks
+        let _all = workers
+            .acquire_many(self.workers as u32)
+            .await
+            .context("Worker pool closed")?;
         self.drain_queue().await;
 
+        let retries = self.retries.lock().await;
+        if !retries.is_empty() {
+            info!(
+                "{} notification(s) waiting for a retry stay spooled until the next start",
+                retries.len()
+            );
+        }
+
         info!("Notification daemon stopped");
         Ok(())
     }
 
-    async fn receive_task(&self) -> Option<NotificationTask> {
-        match self.task_receiver.recv_async().await.ok() {
-            Some(task) => {
-                // Decrement queue size when task is dequeued
-                self.queue_size.fetch_sub(1, Ordering::Relaxed);
-                Some(task)
+    /// Deliver a task on a worker, or park it behind its session's current task
+    fn dispatch(self: &Arc<Self>, task: SpooledTask, permit: OwnedSemaphorePermit) {
+        let session = task.task.session_id();
+        if let Some(session) = &session {
+            match self.sessions.lock().unwrap().entry(session.clone()) {
+                Entry::Occupied(mut waiting) => {
+                    waiting.get_mut().push_back(task);
+                    return;
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert(VecDeque::new());
+                }
+            }
+        }
+
+        let daemon = self.clone();
+        tokio::spawn(async move {
+            let mut next = Some(task);
+            while let Some(task) = next {
+                daemon.process_task(task).await;
+                next = session.as_ref().and_then(|session| daemon.next_in_session(session));
+            }
+            drop(permit);
+        });
+    }
+
+    /// Take the next task parked behind a session, releasing the session when none is left
+    fn next_in_session(&self, session: &str) -> Option<SpooledTask> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let next = sessions.get_mut(session).and_then(VecDeque::pop_front);
+        if next.is_none() {
+            sessions.remove(session);
+        }
+        next
+    }
+
+    async fn process_task(&self, mut spooled: SpooledTask) {
+        let started = Instant::now();
+        let (backends, error) = match self.deliver(&spooled).await {
+            Delivery::Sent { backends } => {
+                let latency = started.elapsed();
+                self.stats.record_sent(&spooled.task, latency);
+                self.events.publish(&spooled, EventKind::Sent {
+                    backends,
+                    latency_ms: latency.as_millis() as u64,
+                });
+                // Delivered, so it must not be replayed after a restart
+                if let Err(e) = self.spools.pending.complete(&spooled.id) {
+                    warn!("{:#}", e);
+                }
+                return;
+            }
+            Delivery::Failed { backends, error } => (backends, error),
+            Delivery::Invalid(error) => (Vec::new(), error),
+        };
+
+        self.stats.record_failed(&spooled.task, &error);
+        let policy = *self.retry_policy.read().unwrap();
+        let task = &spooled.task;
+        spooled.attempts.push(DeliveryAttempt {
+            at: chrono::Local::now(),
+            backends: backends.clone(),
+            error: format!("{error:#}"),
+        });
+
+        if backends.is_empty() || task.retry_count >= policy.max_retries {
+            error!(
+                "Failed to send notification for hook {} after {} attempts, moving it to the dead-letter queue: {:#}",
+                task.hook_name,
+                spooled.attempts.len(),
+                error
+            );
+            self.dead_letter(spooled, &backends);
+            return;
+        }
+
+        warn!(
+            "Failed to send notification for hook {} (attempt {}/{}), retrying in {}s: {:#}",
+            task.hook_name,
+            task.retry_count + 1,
+            policy.max_retries + 1,
+            policy.retry_delay.as_secs(),
+            error
+        );
+
+        spooled.task.backends.retain(|name| backends.contains(name));
+        spooled.task.retry_count += 1;
+        match self.spools.pending.update(&spooled) {
+            Ok(()) => {
+                self.stats.record_retry(&spooled.task, &backends);
+                self.events.publish(&spooled, EventKind::Retried {
+                    backends,
+                    attempt: spooled.task.retry_count,
+                    delay_secs: policy.retry_delay.as_secs(),
+                    error: format!("{error:#}"),
+                });
+                self.retries
+                    .lock()
+                    .await
+                    .schedule(spooled, Instant::now() + policy.retry_delay);
+                self.retry_scheduled.notify_one();
             }
-            None => None,
+            Err(e) => {
+                error!("Failed to schedule retry: {:#}", e);
+                self.dead_letter(spooled, &[]);
+            }
+        }
+    }
+
+    /// Forget a watched project configuration that changed on disk
+    ///
+    /// The next task of the project loads it again. Clients are kept only for
+    /// the backends of the daemon's configuration and of the project
+    /// configurations still loaded, so those the change removed are dropped.
+    pub fn project_config_changed(&self, path: &Path) {
+        let mut project_configs = self.project_configs.lock().unwrap();
+        project_configs.remove(path);
+
+        let mut backends = self.config.read().unwrap().backend_configs();
+        for project in project_configs.values().flatten() {
+            backends.extend(project.backend_configs());
+        }
+        drop(project_configs);
+
+        let evicted = self.clients.read().unwrap().retain(&backends);
+        if evicted > 0 {
+            debug!("Dropped {} backend client(s) no longer configured", evicted);
         }
     }
 
-    async fn process_task(&self, task: NotificationTask) {
+    /// Make one delivery attempt to every backend of a task
+    async fn deliver(&self, spooled: &SpooledTask) -> Delivery {
+        let task = &spooled.task;
         debug!("Processing notification task: {} from project: {:?}", 
                task.hook_name, task.project_path);
 
         // Deserialize hook data from JSON string
         let hook_data: serde_json::Value = match serde_json::from_str(&task.hook_data) {
             Ok(data) => data,
-            Err(e) => {
-                error!("Failed to deserialize hook data: {}", e);
-                return;
-            }
+            Err(e) => return Delivery::Invalid(anyhow::Error::new(e).context("Failed to deserialize hook data")),
         };
 
-        // Create dynamic ntfy client based on task configuration
-        let ntfy_client = match self.create_ntfy_client(&task.ntfy_config).await {
-            Ok(client) => client,
-            Err(e) => {
-                error!("Failed to create ntfy client for task {}: {}", task.hook_name, e);
-                return;
-            }
+        // Create clients for the backends this hook is routed to
+        let registry = match self.create_registry(spooled).await {
+            Ok(registry) => registry,
+            Err(e) => return Delivery::Invalid(e.context("Failed to create backends")),
         };
 
-        // Prepare notification message
-        let message = match self.prepare_message(&task, &hook_data).await {
+        // Prepare notification message once for all backends
+        let message = match task.ntfy_config.render_message(&self.renderer, &task.hook_name, &hook_data) {
             Ok(msg) => msg,
-            Err(e) => {
-                error!(
-                    "Failed to prepare message for hook {}: {}",
-                    task.hook_name, e
+            Err(e) => return Delivery::Invalid(e.context("Failed to prepare message")),
+        };
+        self.events.publish(spooled, EventKind::Rendered {
+            title: message.title.clone().unwrap_or_default(),
+        });
+
+        // Send once; backends that fail are retried later without blocking the queue
+        let event = HookEvent::new(&task.hook_name, hook_data);
+        let results = registry.send_to(&registry.names(), &message, &event).await;
+        for result in &results {
+            self.stats.record_backend(result);
+        }
+        let (sent, backends): (Vec<_>, Vec<_>) = results.iter().partition(|r| r.result.is_ok());
+        let sent: Vec<String> = sent.into_iter().map(|r| r.backend.clone()).collect();
+        let backends: Vec<String> = backends.into_iter().map(|r| r.backend.clone()).collect();
+
+        match combine_results(results) {
+            Ok(()) => {
+                info!(
+                    "Successfully sent notification for hook: {}",
+                    task.hook_name
                 );
-                return;
+                Delivery::Sent { backends: sent }
             }
-        };
+            Err(error) => Delivery::Failed { backends, error },
+        }
+    }
 
-        // Send notification with retry logic
-        let mut attempt = 0;
-        loop {
-            match ntfy_client.send(&message).await {
-                Ok(_) => {
-                    info!(
-                        "Successfully sent notification for hook: {}",
-                        task.hook_name
-                    );
-                    break;
-                }
-                Err(e) => {
-                    attempt += 1;
-                    if attempt > self.max_retries {
-                        error!(
-                            "Failed to send notification for hook {} after {} attempts: {}",
-                            task.hook_name, self.max_retries, e
-                        );
-                        break;
-                    }
-
-                    warn!(
-                        "Failed to send notification for hook {} (attempt {}/{}): {}",
-                        task.hook_name, attempt, self.max_retries, e
-                    );
-
-                    sleep(self.retry_delay).await;
-                }
-            }
+    /// Move a task that failed for good from the spool to the dead-letter queue
+    ///
+    /// Only the backends that failed are kept, so retrying a dead letter does
+    /// not notify the others twice.
+    fn dead_letter(&self, mut spooled: SpooledTask, failed: &[String]) {
+        if !failed.is_empty() {
+            spooled.task.backends.retain(|name| failed.contains(name));
         }
+
+        if let Err(e) = self.spools.bury(&spooled) {
+            error!("{:#}", e);
+        }
+
+        let error = spooled.attempts.last().map(|attempt| attempt.error.clone()).unwrap_or_default();
+        self.events.publish(&spooled, EventKind::Failed { error });
     }
 
-    /// Create ntfy client dynamically based on task configuration
-    async fn create_ntfy_client(&self, config: &super::shared::NtfyTaskConfig) -> Result<AsyncNtfyClient> {
-        use crate::shared::clients::ntfy::NtfyClientConfig;
-        use crate::shared::clients::traits::RetryConfig;
-        
-        let client_config = NtfyClientConfig {
-            server_url: config.server_url.clone(),
-            auth_token: config.auth_token.clone(),
-            timeout_secs: Some(30), // Default timeout
-            retry_config: RetryConfig::exponential(3, 1000), // 3 retries, 1s base delay
-            user_agent: Some("claude-ntfy-service".to_string()),
-        };
+    /// Move every scheduled retry that is due back into the queue
+    async fn requeue_due_retries(&self) {
+        let due = self.retries.lock().await.pop_due(Instant::now());
+        for task in due {
+            self.queue.push(task);
+        }
+    }
 
-        let client = AsyncNtfyClient::new(client_config)
-            .context("Failed to create ntfy client")?;
-        
-        Ok(client)
+    /// Look up backend clients for the task's backends, creating missing ones
+    async fn create_registry(&self, spooled: &SpooledTask) -> Result<BackendRegistry> {
+        let task = &spooled.task;
+        let backends = self.task_config(spooled).await?.backends_named(&task.backends)?;
+
+        let clients = self.clients.read().unwrap().clone();
+        clients.registry(&backends, &task.ntfy_config.retry_config())
     }
 
-    async fn prepare_message(&self, task: &NotificationTask, hook_data: &serde_json::Value) -> Result<NtfyMessage> {
-
-        // Get template name and render message body  
-        let template_name = task.hook_name.replace('_', "-");
-        let formatted_data = self
-            .template_engine
-            .format_hook_data(&task.hook_name, hook_data);
-
-        // Use default template rendering (no custom templates in global daemon)
-        let body = self.template_engine
-            .render(&template_name, &formatted_data)
-            .unwrap_or_else(|_| {
-                // Fallback to simple message if template fails
-                format!("Hook: {}\nData: {}", task.hook_name, hook_data)
-            });
-
-        // Format title
-        let title = self
-            .message_formatter
-            .format_title(&task.hook_name, &formatted_data);
-
-        // Get configuration from task (no longer from config_manager)
-        let topic = &task.ntfy_config.topic;
-        let priority = task.ntfy_config.priority.unwrap_or(3);
-        let tags = task.ntfy_config.tags.clone();
-
-        Ok(NtfyMessage {
-            topic: topic.clone(),
-            title: Some(title),
-            message: body,
-            priority: Some(priority),
-            tags,
-            click: None,
-            attach: None,
-            filename: None,
-            delay: None,
-            email: None,
-            call: None,
-            actions: None,
-            markdown: Some(true),
+    /// Configuration the backends of a task are resolved against
+    ///
+    /// A project with a configuration file of its own uses it, like its hooks
+    /// do; other tasks use the daemon's configuration. The project path of a
+    /// remote task names a directory on another host, and trusting it would
+    /// let remote clients pick any backend on this one, so it is ignored.
+    ///
+    /// Project configurations are cached while the watcher can tell when
+    /// they change, and loaded again for every task otherwise.
+    async fn task_config(&self, spooled: &SpooledTask) -> Result<Arc<ConfigManager>> {
+        let Some(project_path) = spooled.task.project_path.as_ref().filter(|_| !spooled.remote) else {
+            return Ok(self.config.read().unwrap().clone());
+        };
+        let path = ConfigManager::project_config_path(Path::new(project_path));
+        if let Some(project) = self.project_configs.lock().unwrap().get(&path) {
+            return Ok(project.clone().unwrap_or_else(|| self.config.read().unwrap().clone()));
+        }
+
+        // Watch before loading, so an edit made while loading is seen later
+        let watched = self
+            .watcher
+            .as_ref()
+            .is_some_and(|watcher| watcher.watch_project(Path::new(project_path)));
+        let project = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || path.exists().then(|| ConfigManager::load(path)).transpose()
         })
+        .await
+        .context("Project configuration loader panicked")??
+        .map(Arc::new);
+
+        if watched {
+            self.project_configs.lock().unwrap().insert(path, project.clone());
+        }
+        Ok(project.unwrap_or_else(|| self.config.read().unwrap().clone()))
     }
 
     async fn drain_queue(&self) {
-        info!("Draining remaining notification queue");
+        if !self.queue.is_empty() {
+            info!("Draining {} queued notification(s)", self.queue.len());
+        }
 
-        while let Ok(task) = self.task_receiver.try_recv() {
-            // Decrement queue size when task is dequeued during drain
-            self.queue_size.fetch_sub(1, Ordering::Relaxed);
+        while let Some(task) = self.queue.try_pop() {
             self.process_task(task).await;
         }
     }
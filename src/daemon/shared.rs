@@ -4,6 +4,12 @@
 //! organized into logical groups for better maintainability.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::spool::SpooledTask;
+use crate::ntfy::NtfyMessage;
+use crate::shared::clients::traits::RetryConfig;
+use crate::shared::templates::{NotificationRenderer, TemplateStyle};
 
 // =============================================================================
 // Constants
@@ -41,22 +47,77 @@ pub struct NtfyTaskConfig {
     
     /// Authentication token for private topics
     pub auth_token: Option<String>,
+
+    /// Request timeout for the ntfy server in seconds
+    pub timeout_secs: Option<u64>,
+
+    /// The project's custom body template for this hook, if enabled
+    pub custom_template: Option<String>,
+
+    /// The project's template variables
+    pub variables: HashMap<String, String>,
+
+    /// Style of the built-in tags
+    #[serde(default)]
+    pub style: TemplateStyle,
+
+    /// Per-send retries after the first attempt
+    pub retry_attempts: u32,
+
+    /// Base delay for exponential backoff between per-send retries, in milliseconds
+    pub retry_base_delay_ms: u64,
 }
 
 impl NtfyTaskConfig {
     /// Create a new configuration with minimum required fields
     pub fn new(server_url: impl Into<String>, topic: impl Into<String>) -> Self {
+        let retry = RetryConfig::default();
         Self {
             server_url: server_url.into(),
             topic: topic.into(),
             priority: Some(defaults::PRIORITY),
             tags: None,
             auth_token: None,
+            timeout_secs: None,
+            custom_template: None,
+            variables: HashMap::new(),
+            style: TemplateStyle::default(),
+            retry_attempts: retry.max_attempts,
+            retry_base_delay_ms: retry.base_delay_ms,
         }
     }
-    
-    
-    
+
+    /// Per-send retry policy for the backend clients
+    pub fn retry_config(&self) -> RetryConfig {
+        RetryConfig::exponential(self.retry_attempts, self.retry_base_delay_ms)
+    }
+
+    /// Render the notification for a hook with this profile
+    ///
+    /// The hook's built-in tags are used, falling back to `tags`.
+    pub fn render_message(
+        &self,
+        renderer: &NotificationRenderer,
+        hook_name: &str,
+        hook_data: &serde_json::Value,
+    ) -> anyhow::Result<NtfyMessage> {
+        let (title, body) = renderer.render(hook_name, hook_data, self.custom_template.as_deref(), &self.variables)?;
+
+        let mut tags = renderer.tags(hook_name, self.style);
+        if tags.is_empty() {
+            tags = self.tags.clone().unwrap_or_default();
+        }
+
+        Ok(NtfyMessage {
+            topic: self.topic.clone(),
+            title: Some(title),
+            message: body,
+            priority: Some(self.priority.unwrap_or(defaults::PRIORITY)),
+            tags: Some(tags),
+            markdown: Some(true),
+            ..Default::default()
+        })
+    }
 }
 
 impl Default for NtfyTaskConfig {
@@ -92,9 +153,19 @@ pub struct NotificationTask {
     
     /// Source project path (for logging and debugging)
     pub project_path: Option<String>,
+    
+    /// Names of the backends to deliver to
+    ///
+    /// The daemon resolves the names against its configuration.
+    pub backends: Vec<String>,
 }
 
 impl NotificationTask {
+    /// Claude Code session the hook belongs to, if the hook data names one
+    pub fn session_id(&self) -> Option<String> {
+        let data: serde_json::Value = serde_json::from_str(&self.hook_data).ok()?;
+        data.get("session_id")?.as_str().map(str::to_string)
+    }
 }
 
 // =============================================================================
@@ -120,6 +191,38 @@ pub enum DaemonMessage {
     
     /// Request daemon status information
     Status,
+
+    /// List tasks in the dead-letter queue
+    DeadLetters,
+
+    /// Queue dead letters for delivery again, all of them when no id is given
+    RetryDeadLetters(Option<String>),
+
+    /// Delete dead letters, all of them when no id is given
+    PurgeDeadLetters(Option<String>),
+
+    /// Keep the connection open and stream matching events
+    ///
+    /// The daemon acknowledges with `Ok`, then sends `Event` responses until
+    /// either side closes the connection.
+    Subscribe(EventFilter),
+}
+
+impl DaemonMessage {
+    /// The daemon capability needed to handle this message
+    pub fn capability(&self) -> &'static str {
+        match self {
+            DaemonMessage::Submit(_) => "submit",
+            DaemonMessage::Ping => "ping",
+            DaemonMessage::Shutdown => "shutdown",
+            DaemonMessage::Reload => "reload",
+            DaemonMessage::Status => "status",
+            DaemonMessage::DeadLetters
+            | DaemonMessage::RetryDeadLetters(_)
+            | DaemonMessage::PurgeDeadLetters(_) => "dead-letters",
+            DaemonMessage::Subscribe(_) => "subscribe",
+        }
+    }
 }
 
 
@@ -135,14 +238,116 @@ pub enum DaemonResponse {
     Error(String),
     
     /// Status information response
-    Status {
-        /// Number of tasks in the queue
-        queue_size: usize,
-        /// Whether the daemon is running
-        is_running: bool,
-        /// Daemon uptime in seconds
-        uptime_secs: u64,
+    Status(Box<DaemonStatus>),
+
+    /// Tasks in the dead-letter queue, oldest first
+    DeadLetters(Vec<SpooledTask>),
+
+    /// Number of dead letters retried or purged
+    DeadLetterCount(usize),
+
+    /// An event streamed to a subscriber
+    Event(Box<DaemonEvent>),
+}
+
+/// Something that happened to a task in the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonEvent {
+    pub at: chrono::DateTime<chrono::Local>,
+    /// Spool id of the task
+    pub task_id: String,
+    pub hook_name: String,
+    pub project_path: Option<String>,
+    pub kind: EventKind,
+}
+
+/// What happened to a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// Accepted over IPC and spooled
+    Received,
+    /// Message rendered, about to be sent
+    Rendered { title: String },
+    /// Delivered to every backend
+    Sent { backends: Vec<String>, latency_ms: u64 },
+    /// Some backends failed; they are tried again after a delay
+    Retried {
+        backends: Vec<String>,
+        attempt: u32,
+        delay_secs: u64,
+        error: String,
     },
+    /// Given up on and moved to the dead-letter queue
+    Failed { error: String },
+}
+
+/// Which events a subscriber receives
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Only tasks whose project path contains this
+    pub project: Option<String>,
+    /// Only tasks for this hook
+    pub hook: Option<String>,
+}
+
+impl EventFilter {
+    /// Whether `event` passes the filter
+    pub fn matches(&self, event: &DaemonEvent) -> bool {
+        let project_matches = self.project.as_ref().is_none_or(|project| {
+            event
+                .project_path
+                .as_ref()
+                .is_some_and(|path| path.contains(project.as_str()))
+        });
+        let hook_matches = self.hook.as_ref().is_none_or(|hook| *hook == event.hook_name);
+        project_matches && hook_matches
+    }
+}
+
+/// Daemon status with delivery statistics since it started
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Number of tasks in the queue
+    pub queue_size: usize,
+    /// Number of tasks waiting for a daemon-level retry
+    pub retry_queue_size: usize,
+    /// Whether the daemon is running
+    pub is_running: bool,
+    /// Daemon uptime in seconds
+    pub uptime_secs: u64,
+    /// Number of delivery workers
+    pub workers: usize,
+    /// Statistics by project path
+    pub projects: Vec<DeliveryStats>,
+    /// Statistics by hook name
+    pub hooks: Vec<DeliveryStats>,
+    /// Statistics by backend name
+    pub backends: Vec<DeliveryStats>,
+}
+
+/// Delivery statistics for one project, hook or backend
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeliveryStats {
+    pub name: String,
+    /// Successful deliveries
+    pub sent: u64,
+    /// Failed delivery attempts
+    pub failed: u64,
+    /// Daemon-level retries scheduled
+    pub retries: u64,
+    pub last_error: Option<String>,
+    /// Latency of recent successful deliveries, when there are any
+    pub latency: Option<LatencySummary>,
+}
+
+/// Latency percentiles in milliseconds
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
 }
 
 
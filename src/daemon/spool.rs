@@ -0,0 +1,269 @@
+//! Write-ahead spool for queued notification tasks
+//!
+//! Every task accepted over IPC is written to the spool directory next to
+//! `daemon.sock` before it is acknowledged, and removed once it has been
+//! delivered or given up on. Tasks still in the spool when the daemon starts
+//! were interrupted by a crash or kill and are queued again.
+//!
+//! Each task is a JSON file named after its arrival time, so a directory
+//! listing gives the original order. Files are written to a temporary name,
+//! synced and renamed, so a crash never leaves a half-written task behind.
+//!
+//! Tasks that fail permanently are moved to a second spool, the dead-letter
+//! queue, together with the history of their failed attempts.
+//!
+//! Tasks can carry ntfy credentials, so both directories are readable by
+//! their owner only, like the daemon's socket.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{OpenOptions, Permissions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::warn;
+
+use super::shared::NotificationTask;
+
+const EXTENSION: &str = "task";
+
+/// A task together with the spool entry that keeps it durable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpooledTask {
+    pub id: String,
+    pub task: NotificationTask,
+    /// Failed delivery attempts, oldest first
+    #[serde(default)]
+    pub attempts: Vec<DeliveryAttempt>,
+    /// Submitted over TCP, so only the daemon's own backends may deliver it
+    #[serde(default)]
+    pub remote: bool,
+}
+
+/// A failed attempt to deliver a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryAttempt {
+    pub at: DateTime<Local>,
+    /// Backends that failed; empty when the task itself could not be prepared
+    pub backends: Vec<String>,
+    pub error: String,
+}
+
+/// Directory of tasks that have been accepted but not yet finished
+pub struct Spool {
+    dir: PathBuf,
+    next_sequence: AtomicU64,
+}
+
+impl Spool {
+    /// Open the spool directory, creating it if needed and restricting it to its owner
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .and_then(|_| std::fs::set_permissions(&dir, Permissions::from_mode(0o700)))
+            .with_context(|| format!("Failed to create spool directory {}", dir.display()))?;
+
+        Ok(Self {
+            dir,
+            next_sequence: AtomicU64::new(0),
+        })
+    }
+
+    /// The spool directory used by the daemon listening on `socket_path`
+    pub fn dir_for_socket(socket_path: &Path) -> PathBuf {
+        socket_path.with_file_name("spool")
+    }
+
+
+    /// Durably store a new task
+    ///
+    /// Hooks spool tasks from their own processes while the daemon is down,
+    /// so ids include the process id as well as the arrival time.
+    pub fn persist(&self, task: NotificationTask) -> Result<SpooledTask> {
+        self.store(task, false)
+    }
+
+    /// Durably store a new task submitted by a remote client
+    pub fn persist_remote(&self, task: NotificationTask) -> Result<SpooledTask> {
+        self.store(task, true)
+    }
+
+    fn store(&self, task: NotificationTask, remote: bool) -> Result<SpooledTask> {
+        let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        let spooled = SpooledTask {
+            id: format!("{nanos:020}-{}-{sequence:06}", std::process::id()),
+            task,
+            attempts: Vec::new(),
+            remote,
+        };
+        self.update(&spooled)?;
+        Ok(spooled)
+    }
+
+    /// Store a task under its id, replacing any previous copy
+    pub fn update(&self, spooled: &SpooledTask) -> Result<()> {
+        let data = serde_json::to_vec(spooled).context("Failed to serialize task")?;
+        let path = self.path(&spooled.id);
+        let temp_path = path.with_extension("tmp");
+
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&temp_path)
+            .and_then(|mut file| {
+                // The mode only applies to newly created files
+                file.set_permissions(Permissions::from_mode(0o600))?;
+                file.write_all(&data)?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temp_path, &path))
+            .with_context(|| format!("Failed to write spool entry {}", path.display()))
+    }
+
+    /// Remove a stored task, e.g. once it has been delivered
+    pub fn complete(&self, id: &str) -> Result<()> {
+        let path = self.path(id);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove spool entry {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Every stored task, oldest first
+    ///
+    /// Entries that cannot be read are renamed to `*.corrupt` and skipped.
+    pub fn pending(&self) -> Result<Vec<SpooledTask>> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read spool directory {}", self.dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .collect();
+        paths.sort();
+
+        let mut tasks = Vec::new();
+        for path in paths {
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let task = std::fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|data| Ok(serde_json::from_slice::<SpooledTask>(&data)?));
+            match task {
+                // The file name is authoritative, e.g. for copied entries
+                Ok(task) => tasks.push(SpooledTask {
+                    id: id.to_string(),
+                    ..task
+                }),
+                Err(e) => {
+                    warn!("Skipping unreadable spool entry {}: {:#}", path.display(), e);
+                    let _ = std::fs::rename(&path, path.with_extension("corrupt"));
+                }
+            }
+        }
+        Ok(tasks)
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.{EXTENSION}"))
+    }
+}
+
+/// The spool and dead-letter queue of a daemon
+pub struct Spools {
+    pub pending: Spool,
+    pub dead_letters: Spool,
+}
+
+impl Spools {
+    /// Open the directories next to the daemon's `socket_path`
+    pub fn open_for_socket(socket_path: &Path) -> Result<Self> {
+        Ok(Self {
+            pending: Spool::open(Spool::dir_for_socket(socket_path))?,
+            dead_letters: Spool::open(socket_path.with_file_name("dead-letters"))?,
+        })
+    }
+
+    /// Move a task that failed for good to the dead-letter queue
+    ///
+    /// When the dead letter cannot be written the task stays in the spool,
+    /// to be tried again after a restart.
+    pub fn bury(&self, spooled: &SpooledTask) -> Result<()> {
+        self.dead_letters
+            .update(spooled)
+            .with_context(|| format!("Failed to store dead letter {}", spooled.id))?;
+        self.pending.complete(&spooled.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::NtfyTaskConfig;
+    use tempfile::TempDir;
+
+    fn task(hook_name: &str) -> NotificationTask {
+        NotificationTask {
+            hook_name: hook_name.to_string(),
+            hook_data: "{}".to_string(),
+            retry_count: 0,
+            timestamp: chrono::Local::now(),
+            ntfy_config: NtfyTaskConfig::default(),
+            project_path: None,
+            backends: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pending_survives_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        let spool = Spool::open(temp_dir.path()).unwrap();
+        let first = spool.persist(task("Notification")).unwrap();
+        let mut second = spool.persist(task("Stop")).unwrap();
+        spool.persist_remote(task("PostToolUse")).unwrap();
+
+        spool.complete(&first.id).unwrap();
+        second.task.retry_count = 2;
+        spool.update(&second).unwrap();
+
+        let pending = Spool::open(temp_dir.path()).unwrap().pending().unwrap();
+        let names: Vec<&str> = pending.iter().map(|t| t.task.hook_name.as_str()).collect();
+        assert_eq!(names, vec!["Stop", "PostToolUse"]);
+        assert_eq!(pending[0].id, second.id);
+        assert_eq!(pending[0].task.retry_count, 2);
+        assert!(!pending[0].remote && pending[1].remote);
+    }
+
+    #[test]
+    fn test_entries_are_private() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("spool");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::set_permissions(&dir, Permissions::from_mode(0o755)).unwrap();
+
+        let spooled = Spool::open(&dir).unwrap().persist(task("Stop")).unwrap();
+        let mode = |path: PathBuf| std::fs::metadata(path).unwrap().mode() & 0o777;
+        assert_eq!(mode(dir.clone()), 0o700);
+        assert_eq!(mode(dir.join(format!("{}.{EXTENSION}", spooled.id))), 0o600);
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_set_aside() {
+        let temp_dir = TempDir::new().unwrap();
+        let spool = Spool::open(temp_dir.path()).unwrap();
+        spool.persist(task("Stop")).unwrap();
+        std::fs::write(temp_dir.path().join("00000000000000000000-000000.task"), "not json").unwrap();
+
+        let pending = spool.pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert!(temp_dir.path().join("00000000000000000000-000000.corrupt").exists());
+    }
+}
@@ -0,0 +1,172 @@
+//! Delivery statistics reported by `claude-ntfy daemon status`
+//!
+//! Every delivery attempt is counted three ways: by the project that sent
+//! it, by hook name and by backend. Project and hook figures describe whole
+//! tasks, backend figures each backend a task was sent to. Statistics live
+//! in memory and start over when the daemon restarts.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::shared::{DeliveryStats, LatencySummary, NotificationTask};
+use crate::shared::clients::registry::BackendResult;
+use crate::shared::clients::traits::ClientStats;
+
+/// Shown instead of a project path for tasks sent without one
+const NO_PROJECT: &str = "(global)";
+
+#[derive(Default)]
+struct Tables {
+    projects: BTreeMap<String, ClientStats>,
+    hooks: BTreeMap<String, ClientStats>,
+    backends: BTreeMap<String, ClientStats>,
+}
+
+impl Tables {
+    /// Statistics of the project and the hook of a task
+    fn task_entries(&mut self, task: &NotificationTask) -> [&mut ClientStats; 2] {
+        let project = task.project_path.as_deref().unwrap_or(NO_PROJECT).to_string();
+        [
+            self.projects.entry(project).or_default(),
+            self.hooks.entry(task.hook_name.clone()).or_default(),
+        ]
+    }
+}
+
+/// Statistics collected by the daemon since it started
+#[derive(Default)]
+pub struct StatsCollector {
+    tables: Mutex<Tables>,
+}
+
+impl StatsCollector {
+    /// Record the outcome of sending a task to one backend
+    pub fn record_backend(&self, result: &BackendResult) {
+        let mut tables = self.tables.lock().unwrap();
+        let stats = tables.backends.entry(result.backend.clone()).or_default();
+        match &result.result {
+            Ok(()) => stats.record_success(millis(result.latency)),
+            Err(e) => stats.record_failure(format!("{e:#}")),
+        }
+    }
+
+    /// Record a task delivered to all of its backends
+    pub fn record_sent(&self, task: &NotificationTask, latency: Duration) {
+        let mut tables = self.tables.lock().unwrap();
+        for stats in tables.task_entries(task) {
+            stats.record_success(millis(latency));
+        }
+    }
+
+    /// Record a failed attempt to deliver a task
+    pub fn record_failed(&self, task: &NotificationTask, error: &anyhow::Error) {
+        let mut tables = self.tables.lock().unwrap();
+        for stats in tables.task_entries(task) {
+            stats.record_failure(format!("{error:#}"));
+        }
+    }
+
+    /// Record a daemon-level retry scheduled for the failed backends of a task
+    pub fn record_retry(&self, task: &NotificationTask, backends: &[String]) {
+        let mut tables = self.tables.lock().unwrap();
+        for stats in tables.task_entries(task) {
+            stats.record_retry();
+        }
+        for backend in backends {
+            tables.backends.entry(backend.clone()).or_default().record_retry();
+        }
+    }
+
+    /// Statistics by project, hook and backend, each sorted by name
+    pub fn report(&self) -> [Vec<DeliveryStats>; 3] {
+        let tables = self.tables.lock().unwrap();
+        [&tables.projects, &tables.hooks, &tables.backends].map(|table| {
+            table
+                .iter()
+                .map(|(name, stats)| delivery_stats(name, stats))
+                .collect()
+        })
+    }
+}
+
+fn delivery_stats(name: &str, stats: &ClientStats) -> DeliveryStats {
+    let latency = stats.latency_percentile(50.0).map(|p50_ms| LatencySummary {
+        p50_ms,
+        p90_ms: stats.latency_percentile(90.0).unwrap_or(p50_ms),
+        p99_ms: stats.latency_percentile(99.0).unwrap_or(p50_ms),
+        max_ms: stats.max_latency_ms,
+    });
+
+    DeliveryStats {
+        name: name.to_string(),
+        sent: stats.messages_sent,
+        failed: stats.messages_failed,
+        retries: stats.retry_attempts,
+        last_error: stats.last_error.clone(),
+        latency,
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::NtfyTaskConfig;
+
+    fn task(project_path: Option<&str>, hook_name: &str) -> NotificationTask {
+        NotificationTask {
+            hook_name: hook_name.to_string(),
+            hook_data: "{}".to_string(),
+            retry_count: 0,
+            timestamp: chrono::Local::now(),
+            ntfy_config: NtfyTaskConfig::default(),
+            project_path: project_path.map(str::to_string),
+            backends: Vec::new(),
+        }
+    }
+
+    fn backend(name: &str, result: anyhow::Result<()>, latency_ms: u64) -> BackendResult {
+        BackendResult {
+            backend: name.to_string(),
+            result,
+            latency: Duration::from_millis(latency_ms),
+        }
+    }
+
+    #[test]
+    fn test_report_by_project_hook_and_backend() {
+        let stats = StatsCollector::default();
+        let stop = task(Some("/work/api"), "Stop");
+        for latency_ms in [10, 20, 30, 400] {
+            stats.record_backend(&backend("phone", Ok(()), latency_ms));
+            stats.record_sent(&stop, Duration::from_millis(latency_ms));
+        }
+
+        let edit = task(None, "PostToolUse");
+        stats.record_backend(&backend("phone", Ok(()), 5));
+        stats.record_backend(&backend("incidents", Err(anyhow::anyhow!("HTTP 503")), 50));
+        stats.record_failed(&edit, &anyhow::anyhow!("Failed to deliver to incidents"));
+        stats.record_retry(&edit, &["incidents".to_string()]);
+
+        let [projects, hooks, backends] = stats.report();
+        let names = |entries: &[DeliveryStats]| entries.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&projects), vec!["(global)", "/work/api"]);
+        assert_eq!(names(&hooks), vec!["PostToolUse", "Stop"]);
+        assert_eq!(names(&backends), vec!["incidents", "phone"]);
+
+        let api = &projects[1];
+        assert_eq!((api.sent, api.failed, api.retries), (4, 0, 0));
+        let latency = api.latency.unwrap();
+        assert_eq!((latency.p50_ms, latency.p90_ms, latency.max_ms), (20, 400, 400));
+
+        let incidents = &backends[0];
+        assert_eq!((incidents.sent, incidents.failed, incidents.retries), (0, 1, 1));
+        assert_eq!(incidents.last_error.as_deref(), Some("HTTP 503"));
+        assert!(incidents.latency.is_none());
+        assert_eq!(backends[1].sent, 5);
+    }
+}
@@ -0,0 +1,131 @@
+//! Polling watcher for configuration files
+//!
+//! With `daemon.watch_config` the daemon checks its own configuration file
+//! and the configuration of the projects it has delivered local tasks for, up
+//! to [`MAX_PROJECTS`], so edits take effect without `claude-ntfy daemon
+//! reload`. Files are compared by modification time and size on a short
+//! interval, which works the same for editors that rewrite in place and those
+//! that replace the file.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::config::ConfigManager;
+
+/// Most project configurations watched at once; further projects are not watched
+pub const MAX_PROJECTS: usize = 64;
+
+/// What a file looked like when last checked; `None` while it does not exist
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Configuration files to check for changes
+#[derive(Default)]
+pub struct ConfigWatcher {
+    files: Mutex<HashMap<PathBuf, Stamp>>,
+    /// Project configurations among `files`
+    projects: Mutex<HashSet<PathBuf>>,
+}
+
+impl ConfigWatcher {
+    /// Start watching a file, unless it is already watched
+    pub fn watch(&self, path: PathBuf) {
+        if let Entry::Vacant(entry) = self.files.lock().unwrap().entry(path) {
+            let stamp = stamp(entry.key());
+            entry.insert(stamp);
+        }
+    }
+
+    /// Start watching the configuration of the project at `project_path`
+    ///
+    /// Once [`MAX_PROJECTS`] projects are watched, further ones are ignored.
+    /// Returns whether the project's configuration is watched.
+    pub fn watch_project(&self, project_path: &Path) -> bool {
+        let path = ConfigManager::project_config_path(project_path);
+        let mut projects = self.projects.lock().unwrap();
+        if projects.contains(&path) {
+            return true;
+        }
+        if projects.len() >= MAX_PROJECTS {
+            return false;
+        }
+        projects.insert(path.clone());
+        self.watch(path);
+        true
+    }
+
+    /// Files that were created, modified or removed since the last check
+    pub fn changed(&self) -> Vec<PathBuf> {
+        let mut files = self.files.lock().unwrap();
+        let mut changed = Vec::new();
+        for (path, last) in files.iter_mut() {
+            let current = stamp(path);
+            if current != *last {
+                *last = current;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /// Check for changes every `interval`, calling `on_change` for each changed file
+    pub async fn run(&self, interval: Duration, on_change: impl Fn(&Path)) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for path in self.changed() {
+                on_change(&path);
+            }
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_changed_files_reported_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = temp_dir.path().join("config.toml");
+        std::fs::write(&global, "[daemon]").unwrap();
+
+        let watcher = ConfigWatcher::default();
+        watcher.watch(global.clone());
+        watcher.watch_project(temp_dir.path());
+        assert!(watcher.changed().is_empty());
+
+        std::fs::write(&global, "[daemon]\nworkers = 2").unwrap();
+        assert_eq!(watcher.changed(), vec![global.clone()]);
+        assert!(watcher.changed().is_empty());
+
+        // A project config that appears later counts as a change too
+        let project = temp_dir.path().join(".claude/ntfy-service/config.toml");
+        std::fs::create_dir_all(project.parent().unwrap()).unwrap();
+        std::fs::write(&project, "[ntfy]").unwrap();
+        assert_eq!(watcher.changed(), vec![project]);
+
+        std::fs::remove_file(&global).unwrap();
+        assert_eq!(watcher.changed(), vec![global]);
+    }
+
+    #[test]
+    fn test_watched_projects_are_capped() {
+        let watcher = ConfigWatcher::default();
+        for project in 0..MAX_PROJECTS {
+            assert!(watcher.watch_project(Path::new(&format!("/projects/{project}"))));
+        }
+        assert!(!watcher.watch_project(Path::new("/projects/extra")));
+        assert!(watcher.watch_project(Path::new("/projects/0")));
+        assert_eq!(watcher.projects.lock().unwrap().len(), MAX_PROJECTS);
+        assert_eq!(watcher.files.lock().unwrap().len(), MAX_PROJECTS);
+    }
+}
@@ -33,7 +33,7 @@ mod tests {
             AppError::Other { message, .. } => {
                 assert_eq!(message, "test error");
             },
-            _ => assert!(false, "Expected AppError::Other, got {:?}", app_err),
+            _ => panic!("Expected AppError::Other, got {:?}", app_err),
         }
     }
 }
\ No newline at end of file
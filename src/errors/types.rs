@@ -139,7 +139,7 @@ mod tests {
             AppError::Io { operation, .. } => {
                 assert_eq!(operation, "file not found");
             },
-            _ => assert!(false, "Expected AppError::Io, got {:?}", app_err),
+            _ => panic!("Expected AppError::Io, got {:?}", app_err),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+//! Hook decision output
+//!
+//! This module contains the JSON structures Claude Code reads from a hook's
+//! stdout to control execution, such as PreToolUse permission decisions.
+
+use serde::{Deserialize, Serialize};
+
+/// Permission decision for a PreToolUse hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionDecision {
+    /// Bypass the permission system and run the tool
+    Allow,
+    /// Prevent the tool call
+    Deny,
+    /// Fall back to Claude Code's interactive permission prompt
+    #[default]
+    Ask,
+}
+
+impl PermissionDecision {
+    /// Parse a decision keyword from a reply message
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword.to_lowercase().as_str() {
+            "allow" | "approve" | "yes" => Some(Self::Allow),
+            "deny" | "reject" | "no" => Some(Self::Deny),
+            "ask" => Some(Self::Ask),
+            _ => None,
+        }
+    }
+
+    /// The keyword used in reply messages and hook output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+        }
+    }
+}
+
+/// Hook output printed to stdout for Claude Code
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookOutput {
+    pub hook_specific_output: HookSpecificOutput,
+}
+
+/// Event-specific part of the hook output
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookSpecificOutput {
+    pub hook_event_name: String,
+    pub permission_decision: PermissionDecision,
+    pub permission_decision_reason: String,
+}
+
+impl HookOutput {
+    /// Build the output for a PreToolUse permission decision
+    pub fn pre_tool_use(decision: PermissionDecision, reason: impl Into<String>) -> Self {
+        Self {
+            hook_specific_output: HookSpecificOutput {
+                hook_event_name: "PreToolUse".to_string(),
+                permission_decision: decision,
+                permission_decision_reason: reason.into(),
+            },
+        }
+    }
+}
+
+/// Stop hook output that prevents Claude Code from stopping
+#[derive(Debug, Clone, Serialize)]
+pub struct StopOutput {
+    pub decision: String,
+    pub reason: String,
+}
+
+impl StopOutput {
+    /// Block the stop and hand `reason` to Claude as its next instruction
+    pub fn block(reason: impl Into<String>) -> Self {
+        Self {
+            decision: "block".to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Parse an approval reply of the form `<decision> <request_id>`
+///
+/// Returns `None` if the reply is for a different request or malformed.
+pub fn parse_approval_reply(message: &str, request_id: &str) -> Option<PermissionDecision> {
+    let mut parts = message.split_whitespace();
+    let decision = PermissionDecision::from_keyword(parts.next()?)?;
+    (parts.next()? == request_id).then_some(decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pre_tool_use_output_format() {
+        let output = HookOutput::pre_tool_use(PermissionDecision::Deny, "Denied from phone");
+        let json = serde_json::to_value(&output).unwrap();
+
+        assert_eq!(json["hookSpecificOutput"]["hookEventName"], "PreToolUse");
+        assert_eq!(json["hookSpecificOutput"]["permissionDecision"], "deny");
+        assert_eq!(json["hookSpecificOutput"]["permissionDecisionReason"], "Denied from phone");
+    }
+
+    #[test]
+    fn test_stop_output_format() {
+        let output = StopOutput::block("Also run the tests");
+        let json = serde_json::to_value(&output).unwrap();
+
+        assert_eq!(json, serde_json::json!({"decision": "block", "reason": "Also run the tests"}));
+    }
+
+    #[test]
+    fn test_parse_approval_reply() {
+        assert_eq!(parse_approval_reply("allow abc123", "abc123"), Some(PermissionDecision::Allow));
+        assert_eq!(parse_approval_reply("DENY abc123", "abc123"), Some(PermissionDecision::Deny));
+        assert_eq!(parse_approval_reply("allow other", "abc123"), None);
+        assert_eq!(parse_approval_reply("maybe abc123", "abc123"), None);
+        assert_eq!(parse_approval_reply("allow", "abc123"), None);
+    }
+}
@@ -139,6 +139,34 @@ impl DefaultHookDataEnhancer {
         true
     }
     
+    /// Enhance Stop/SubagentStop hook data
+    ///
+    /// Computes `session_duration` from the first timestamp in the session
+    /// transcript, and `final_status` from whether a stop hook already kept
+    /// the session going.
+    fn enhance_stop(&self, mut data: Value) -> AppResult<Value> {
+        let session_duration = data
+            .get("transcript_path")
+            .and_then(|p| p.as_str())
+            .and_then(session_start_from_transcript)
+            .map(|start| format_duration(chrono::Utc::now() - start));
+
+        let continued = data
+            .get("stop_hook_active")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let final_status = if continued { "continued by hook" } else { "completed" };
+
+        if let Some(obj) = data.as_object_mut() {
+            if let Some(duration) = session_duration {
+                obj.entry("session_duration").or_insert(json!(duration));
+            }
+            obj.entry("final_status").or_insert(json!(final_status));
+        }
+
+        Ok(data)
+    }
+    
     /// Add timestamp fields to hook data
     fn add_timestamp_fields(&self, mut data: Value) -> AppResult<Value> {
         if !self.add_timestamps {
@@ -162,6 +190,36 @@ impl DefaultHookDataEnhancer {
     }
 }
 
+/// Find the session start time from the first timestamped transcript entry
+fn session_start_from_transcript(path: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use std::io::BufRead;
+
+    let file = std::fs::File::open(path).ok()?;
+    std::io::BufReader::new(file)
+        .lines()
+        .take(50)
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+        .find_map(|entry| {
+            let timestamp = entry.get("timestamp")?.as_str()?;
+            chrono::DateTime::parse_from_rfc3339(timestamp).ok()
+        })
+        .map(|start| start.with_timezone(&chrono::Utc))
+}
+
+/// Format a duration compactly, e.g. `1h 05m`, `12m 30s` or `45s`
+fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
 impl Default for DefaultHookDataEnhancer {
     fn default() -> Self {
         Self::new()
@@ -176,6 +234,8 @@ impl HookDataEnhancer for DefaultHookDataEnhancer {
         enhanced_data = match hook_name {
             "PostToolUse" => self.enhance_post_tool_use(enhanced_data)
                 .with_context("Failed to enhance PostToolUse hook data")?,
+            "Stop" | "SubagentStop" => self.enhance_stop(enhanced_data)
+                .with_context("Failed to enhance Stop hook data")?,
             _ => enhanced_data,
         };
         
@@ -205,7 +265,7 @@ mod tests {
         });
         
         let result = enhancer.enhance("PostToolUse", data).unwrap();
-        assert_eq!(result.get("success").unwrap().as_bool().unwrap(), true);
+        assert!(result.get("success").unwrap().as_bool().unwrap());
     }
     
     #[test]
@@ -221,7 +281,7 @@ mod tests {
         });
         
         let result = enhancer.enhance("PostToolUse", data).unwrap();
-        assert_eq!(result.get("success").unwrap().as_bool().unwrap(), false);
+        assert!(!result.get("success").unwrap().as_bool().unwrap());
     }
     
     #[test]
@@ -237,7 +297,7 @@ mod tests {
         });
         
         let result = enhancer.enhance("PostToolUse", data).unwrap();
-        assert_eq!(result.get("success").unwrap().as_bool().unwrap(), true);
+        assert!(result.get("success").unwrap().as_bool().unwrap());
         
         // Test with exit_code != 0 (failure)
         let data = json!({
@@ -248,7 +308,7 @@ mod tests {
         });
         
         let result = enhancer.enhance("PostToolUse", data).unwrap();
-        assert_eq!(result.get("success").unwrap().as_bool().unwrap(), false);
+        assert!(!result.get("success").unwrap().as_bool().unwrap());
     }
     
     #[test]
@@ -275,6 +335,32 @@ mod tests {
         });
         
         let result = enhancer.enhance("PostToolUse", data).unwrap();
-        assert_eq!(result.get("success").unwrap().as_bool().unwrap(), false);
+        assert!(!result.get("success").unwrap().as_bool().unwrap());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_stop_session_summary() {
+        let enhancer = DefaultHookDataEnhancer::new();
+        let mut transcript = tempfile::NamedTempFile::new().unwrap();
+        let start = chrono::Utc::now() - chrono::Duration::seconds(125);
+        use std::io::Write;
+        writeln!(transcript, r#"{{"type":"summary"}}"#).unwrap();
+        writeln!(transcript, r#"{{"type":"user","timestamp":"{}"}}"#, start.to_rfc3339()).unwrap();
+
+        let data = json!({
+            "session_id": "abc",
+            "transcript_path": transcript.path().to_string_lossy(),
+            "stop_hook_active": false
+        });
+
+        let result = enhancer.enhance("Stop", data).unwrap();
+        assert_eq!(result["session_duration"], "2m 05s");
+        assert_eq!(result["final_status"], "completed");
+    }
+    
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::seconds(45)), "45s");
+        assert_eq!(format_duration(chrono::Duration::seconds(3900)), "1h 05m");
+    }
+}
@@ -0,0 +1,320 @@
+//! Hook filtering logic
+//!
+//! This module evaluates the `[hooks.filters]` rules from the configuration
+//! against hook payloads to decide whether a notification should be sent.
+//!
+//! # Rule Syntax
+//!
+//! Each hook maps to a list of rules. A rule is either an include rule or an
+//! exclude rule (prefixed with `!`), and either targets the hook's default
+//! field or an explicit payload field:
+//!
+//! - `Write` - include when the default field matches `Write`
+//! - `!Read` - exclude when the default field matches `Read`
+//! - `tool_input.command=git push*` - include when the dotted field matches
+//! - `!tool_input.file_path=*.lock` - exclude when the dotted field matches
+//!
+//! Patterns support `*` (any sequence) and `?` (any single character).
+//! The default field is `tool_name` for tool hooks, and `prompt`/`message`
+//! for free-text hooks, where a bare pattern matches anywhere in the text
+//! (case-insensitively).
+//!
+//! A hook is filtered out if any exclude rule matches, or if include rules
+//! exist and none of them match.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use crate::errors::{AppError, AppResult};
+
+/// Hooks that block Claude Code until the user responds
+const DECISION_HOOKS: &[&str] = &["Notification"];
+
+/// Determines whether a hook requires a user decision
+///
+/// Decision hooks are the ones where Claude Code is waiting for the user
+/// (e.g. a permission prompt), so they should never be silently dropped.
+pub fn is_decision_hook(hook_name: &str) -> bool {
+    DECISION_HOOKS.contains(&hook_name)
+}
+
+/// A single parsed filter rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterRule {
+    /// Whether the rule excludes matching hooks (`!` prefix)
+    pub exclude: bool,
+
+    /// Dotted payload path to match, or `None` for the hook's default field
+    pub field: Option<String>,
+
+    /// Wildcard pattern to match against the field value
+    pub pattern: String,
+}
+
+impl FilterRule {
+    /// Parse a rule from its configuration string
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if the rule has an empty pattern or field name.
+    pub fn parse(rule: &str) -> AppResult<Self> {
+        let rule = rule.trim();
+        let (exclude, body) = match rule.strip_prefix('!') {
+            Some(rest) => (true, rest.trim()),
+            None => (false, rule),
+        };
+
+        let (field, pattern) = match body.split_once('=') {
+            Some((field, pattern)) => {
+                let field = field.trim();
+                if field.is_empty() {
+                    return Err(AppError::ValidationError(format!(
+                        "Filter rule '{rule}' has an empty field name"
+                    )));
+                }
+                (Some(field.to_string()), pattern.trim())
+            }
+            None => (None, body),
+        };
+
+        if pattern.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "Filter rule '{rule}' has an empty pattern"
+            )));
+        }
+
+        Ok(Self {
+            exclude,
+            field,
+            pattern: pattern.to_string(),
+        })
+    }
+
+    /// Check whether this rule's pattern matches the hook payload
+    ///
+    /// A rule never matches if its target field is missing from the payload.
+    pub fn matches(&self, hook_name: &str, data: &Value) -> bool {
+        match &self.field {
+            Some(field) => lookup_field(data, field)
+                .is_some_and(|value| wildcard_match(&self.pattern, &value)),
+            None => {
+                let (field, substring) = default_field(hook_name);
+                lookup_field(data, field).is_some_and(|value| {
+                    if substring {
+                        let pattern = format!("*{}*", self.pattern.to_lowercase());
+                        wildcard_match(&pattern, &value.to_lowercase())
+                    } else {
+                        wildcard_match(&self.pattern, &value)
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Compiled set of filter rules for all hooks
+#[derive(Debug, Clone, Default)]
+pub struct HookFilter {
+    rules: HashMap<String, Vec<FilterRule>>,
+    never_filter_decision_hooks: bool,
+}
+
+impl HookFilter {
+    /// Compile filter rules from the `[hooks.filters]` configuration
+    ///
+    /// Invalid rules are skipped with a warning so that a single typo does not
+    /// silence every notification.
+    pub fn from_config(filters: &HashMap<String, Vec<String>>, never_filter_decision_hooks: bool) -> Self {
+        let rules = filters
+            .iter()
+            .map(|(hook_name, rules)| {
+                let parsed = rules
+                    .iter()
+                    .filter_map(|rule| match FilterRule::parse(rule) {
+                        Ok(rule) => Some(rule),
+                        Err(e) => {
+                            tracing::warn!("Ignoring invalid filter for {}: {}", hook_name, e);
+                            None
+                        }
+                    })
+                    .collect();
+                (hook_name.clone(), parsed)
+            })
+            .collect();
+
+        Self {
+            rules,
+            never_filter_decision_hooks,
+        }
+    }
+
+    /// Determine whether the hook passes the configured filters
+    pub fn allows(&self, hook_name: &str, data: &Value) -> bool {
+        if self.never_filter_decision_hooks && is_decision_hook(hook_name) {
+            return true;
+        }
+
+        let Some(rules) = self.rules.get(hook_name) else {
+            return true;
+        };
+
+        let mut has_includes = false;
+        let mut included = false;
+        for rule in rules {
+            let matched = rule.matches(hook_name, data);
+            if rule.exclude {
+                if matched {
+                    return false;
+                }
+            } else {
+                has_includes = true;
+                included |= matched;
+            }
+        }
+
+        !has_includes || included
+    }
+}
+
+/// Default field for bare rules and whether it is matched as a substring
+fn default_field(hook_name: &str) -> (&'static str, bool) {
+    match hook_name {
+        "UserPromptSubmit" => ("prompt", true),
+        "Notification" => ("message", true),
+        _ => ("tool_name", false),
+    }
+}
+
+/// Resolve a dotted path in the payload to a string value
+fn lookup_field(data: &Value, path: &str) -> Option<String> {
+    let value = path
+        .split('.')
+        .try_fold(data, |current, key| current.get(key))?;
+
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Match a value against a pattern supporting `*` and `?` wildcards
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = backtrack {
+            p = star_p + 1;
+            v = star_v + 1;
+            backtrack = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(hook_name: &str, rules: &[&str]) -> HookFilter {
+        let mut filters = HashMap::new();
+        filters.insert(
+            hook_name.to_string(),
+            rules.iter().map(|r| r.to_string()).collect(),
+        );
+        HookFilter::from_config(&filters, true)
+    }
+
+    #[test]
+    fn test_rule_parsing() {
+        let rule = FilterRule::parse("!Read").unwrap();
+        assert!(rule.exclude);
+        assert_eq!(rule.field, None);
+        assert_eq!(rule.pattern, "Read");
+
+        let rule = FilterRule::parse("tool_input.command=git push*").unwrap();
+        assert!(!rule.exclude);
+        assert_eq!(rule.field.as_deref(), Some("tool_input.command"));
+        assert_eq!(rule.pattern, "git push*");
+
+        assert!(FilterRule::parse("!").is_err());
+        assert!(FilterRule::parse("=Read").is_err());
+    }
+
+    #[test]
+    fn test_exclude_rules() {
+        let filter = filter("PreToolUse", &["!Read", "!Grep"]);
+
+        assert!(!filter.allows("PreToolUse", &json!({"tool_name": "Read"})));
+        assert!(!filter.allows("PreToolUse", &json!({"tool_name": "Grep"})));
+        assert!(filter.allows("PreToolUse", &json!({"tool_name": "Bash"})));
+        assert!(filter.allows("PostToolUse", &json!({"tool_name": "Read"})));
+    }
+
+    #[test]
+    fn test_include_rules() {
+        let filter = filter("PostToolUse", &["Write", "Edit"]);
+
+        assert!(filter.allows("PostToolUse", &json!({"tool_name": "Write"})));
+        assert!(!filter.allows("PostToolUse", &json!({"tool_name": "Read"})));
+        assert!(!filter.allows("PostToolUse", &json!({})));
+    }
+
+    #[test]
+    fn test_field_rules_with_wildcards() {
+        let filter = filter("PreToolUse", &["!tool_input.command=git status*", "mcp__*", "Bash"]);
+
+        assert!(filter.allows("PreToolUse", &json!({
+            "tool_name": "Bash",
+            "tool_input": {"command": "git push origin main"}
+        })));
+        assert!(!filter.allows("PreToolUse", &json!({
+            "tool_name": "Bash",
+            "tool_input": {"command": "git status --short"}
+        })));
+        assert!(filter.allows("PreToolUse", &json!({"tool_name": "mcp__github__create_issue"})));
+    }
+
+    #[test]
+    fn test_free_text_rules() {
+        let filter = filter("UserPromptSubmit", &["deploy", "!debug"]);
+
+        assert!(filter.allows("UserPromptSubmit", &json!({"prompt": "please deploy to staging"})));
+        assert!(!filter.allows("UserPromptSubmit", &json!({"prompt": "deploy the debug build"})));
+        assert!(!filter.allows("UserPromptSubmit", &json!({"prompt": "refactor the parser"})));
+    }
+
+    #[test]
+    fn test_decision_hooks_bypass_filters() {
+        let mut filters = HashMap::new();
+        filters.insert("Notification".to_string(), vec!["!*".to_string()]);
+
+        let data = json!({"message": "Claude needs your permission to use Bash"});
+        assert!(HookFilter::from_config(&filters, true).allows("Notification", &data));
+        assert!(!HookFilter::from_config(&filters, false).allows("Notification", &data));
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("Read", "Read"));
+        assert!(!wildcard_match("Read", "ReadFile"));
+        assert!(wildcard_match("Read*", "ReadFile"));
+        assert!(wildcard_match("*.rs", "src/main.rs"));
+        assert!(wildcard_match("?ash", "Bash"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("a*b", "acbd"));
+    }
+}
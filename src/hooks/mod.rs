@@ -8,9 +8,12 @@ pub mod types;
 pub mod processor;
 pub mod enhancer;
 pub mod validator;
+pub mod filter;
+pub mod decision;
 
 // Re-export main types and traits for convenient usage
 pub use processor::DefaultHookProcessor;
+pub use filter::HookFilter;
 
 
 /// Create a default hook processor with standard configuration
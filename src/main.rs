@@ -1,12 +1,9 @@
 use anyhow::Result;
 
 mod cli;
-mod config;
-mod daemon;
-mod errors;
-mod hooks;
-mod ntfy;
-mod shared;
+
+// The CLI builds on the library, whose public API is checked there rather than here
+use claude_ntfy::{config, daemon, hooks, ntfy, shared};
 
 use cli::CliApp;
 
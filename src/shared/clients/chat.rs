@@ -0,0 +1,314 @@
+//! Slack and Discord incoming-webhook backends
+//!
+//! Both clients map the rendered [`NtfyMessage`] onto the platform's native
+//! layout: Slack Block Kit inside a colored attachment, and Discord embeds.
+//! The color follows the ntfy priority, the tool name and working directory
+//! from the hook data become fields, and `click` becomes a link.
+//!
+//! Bodies rendered by `TemplateEngine` are HTML-escaped markdown, so they are
+//! unescaped and then re-escaped for each platform: Slack mrkdwn requires
+//! `&`, `<` and `>` to be escaped and uses single `*` for bold, while Discord
+//! renders standard markdown as-is.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use super::format::{truncate, unescape_html};
+use super::http::{build_client, ensure_success};
+use super::traits::{send_with_retry, ClientStats, HookEvent, NotificationClient, RetryConfig};
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::ChatBackendConfig;
+
+/// Hook data shown as fields: (label, key)
+const HOOK_FIELDS: &[(&str, &str)] = &[("Tool", "tool_name"), ("Directory", "cwd")];
+
+// Platform limits
+const SLACK_HEADER_MAX: usize = 150;
+const SLACK_TEXT_MAX: usize = 3000;
+const DISCORD_TITLE_MAX: usize = 256;
+const DISCORD_DESCRIPTION_MAX: usize = 4096;
+const DISCORD_FIELD_MAX: usize = 1024;
+
+/// Slack incoming-webhook client using Block Kit
+pub struct SlackClient {
+    client: Client,
+    config: ChatBackendConfig,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl SlackClient {
+    pub fn new(config: ChatBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        Ok(Self {
+            client: build_client(config.timeout_secs)?,
+            config,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Build the Block Kit payload for a notification
+    fn build_payload(&self, message: &NtfyMessage, event: &HookEvent) -> Value {
+        let title = message.title.clone().unwrap_or_else(|| event.hook_name.clone());
+        let mut blocks = vec![
+            json!({
+                "type": "header",
+                "text": {"type": "plain_text", "text": truncate(&title, SLACK_HEADER_MAX), "emoji": true}
+            }),
+            json!({
+                "type": "section",
+                "text": {"type": "mrkdwn", "text": truncate(&slack_mrkdwn(&message.message), SLACK_TEXT_MAX)}
+            }),
+        ];
+
+        let fields: Vec<Value> = hook_fields(event)
+            .into_iter()
+            .map(|(label, value)| {
+                json!({"type": "mrkdwn", "text": format!("*{label}*\n{}", slack_escape(&code_span(&value)))})
+            })
+            .collect();
+        if !fields.is_empty() {
+            blocks.push(json!({"type": "section", "fields": fields}));
+        }
+
+        if let Some(tags) = message.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            let emoji: Vec<String> = tags.iter().map(|tag| format!(":{tag}:")).collect();
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{"type": "mrkdwn", "text": emoji.join(" ")}]
+            }));
+        }
+
+        if let Some(click) = &message.click {
+            blocks.push(json!({
+                "type": "actions",
+                "elements": [{"type": "button", "text": {"type": "plain_text", "text": "Open"}, "url": click}]
+            }));
+        }
+
+        let mut payload = json!({
+            "text": title,
+            "attachments": [{"color": format!("#{:06x}", priority_color(message.priority)), "blocks": blocks}]
+        });
+        if let Some(username) = &self.config.username {
+            payload["username"] = json!(username);
+        }
+        payload
+    }
+
+    async fn send_internal(&self, payload: &Value) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.config.webhook_url)
+            .json(payload)
+            .send()
+            .await
+            .context("Failed to send Slack webhook request")?;
+
+        ensure_success(response, "Slack").await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for SlackClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        self.send_event(message, &HookEvent::default()).await
+    }
+
+    async fn send_event(&self, message: &NtfyMessage, event: &HookEvent) -> Result<()> {
+        let payload = self.build_payload(message, event);
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&payload)).await
+    }
+}
+
+/// Discord incoming-webhook client using embeds
+pub struct DiscordClient {
+    client: Client,
+    config: ChatBackendConfig,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl DiscordClient {
+    pub fn new(config: ChatBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        Ok(Self {
+            client: build_client(config.timeout_secs)?,
+            config,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Build the embed payload for a notification
+    fn build_payload(&self, message: &NtfyMessage, event: &HookEvent) -> Value {
+        let title = message.title.clone().unwrap_or_else(|| event.hook_name.clone());
+        let fields: Vec<Value> = hook_fields(event)
+            .into_iter()
+            .map(|(label, value)| {
+                json!({"name": label, "value": truncate(&code_span(&value), DISCORD_FIELD_MAX), "inline": true})
+            })
+            .collect();
+
+        let mut embed = json!({
+            "title": truncate(&title, DISCORD_TITLE_MAX),
+            "description": truncate(&unescape_html(&message.message), DISCORD_DESCRIPTION_MAX),
+            "color": priority_color(message.priority),
+            "fields": fields,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        if let Some(click) = &message.click {
+            embed["url"] = json!(click);
+        }
+        if let Some(tags) = message.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            embed["footer"] = json!({"text": tags.join(", ")});
+        }
+
+        // Never ping anyone, whatever the hook output contains
+        let mut payload = json!({"embeds": [embed], "allowed_mentions": {"parse": []}});
+        if let Some(username) = &self.config.username {
+            payload["username"] = json!(username);
+        }
+        payload
+    }
+
+    async fn send_internal(&self, payload: &Value) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.config.webhook_url)
+            .json(payload)
+            .send()
+            .await
+            .context("Failed to send Discord webhook request")?;
+
+        ensure_success(response, "Discord").await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for DiscordClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        self.send_event(message, &HookEvent::default()).await
+    }
+
+    async fn send_event(&self, message: &NtfyMessage, event: &HookEvent) -> Result<()> {
+        let payload = self.build_payload(message, event);
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&payload)).await
+    }
+}
+
+/// RGB color for an ntfy priority (1-5)
+fn priority_color(priority: Option<u8>) -> u32 {
+    match priority.unwrap_or(3) {
+        1 => 0x95a5a6, // min: gray
+        2 => 0x3498db, // low: blue
+        4 => 0xe67e22, // high: orange
+        5 => 0xe74c3c, // urgent: red
+        _ => 0x2ecc71, // default: green
+    }
+}
+
+/// Hook data values shown as fields
+fn hook_fields(event: &HookEvent) -> Vec<(&'static str, String)> {
+    HOOK_FIELDS
+        .iter()
+        .filter_map(|(label, key)| {
+            event
+                .data
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+                .map(|v| (*label, v.to_string()))
+        })
+        .collect()
+}
+
+/// Wrap raw text in a code span so no markdown inside it is interpreted
+fn code_span(value: &str) -> String {
+    format!("`{}`", value.replace('`', "'"))
+}
+
+/// Escape the control characters of Slack's mrkdwn
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Convert a rendered markdown body to Slack mrkdwn
+fn slack_mrkdwn(markdown: &str) -> String {
+    slack_escape(&unescape_html(markdown)).replace("**", "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ChatBackendConfig {
+        ChatBackendConfig {
+            webhook_url: "http://127.0.0.1:9/webhook".to_string(),
+            username: Some("Claude".to_string()),
+            timeout_secs: Some(5),
+        }
+    }
+
+    fn sample() -> (NtfyMessage, HookEvent) {
+        let message = NtfyMessage {
+            title: Some("Tool Completed".to_string()),
+            message: "❌ **Bash** failed\n💻 `cargo test &amp;&amp; echo &quot;done&quot;`".to_string(),
+            priority: Some(5),
+            tags: Some(vec!["warning".to_string()]),
+            click: Some("https://example.com/session".to_string()),
+            ..Default::default()
+        };
+        let event = HookEvent::new("PostToolUse", json!({"tool_name": "Bash", "cwd": "/work/<repo>"}));
+        (message, event)
+    }
+
+    #[test]
+    fn test_slack_payload() {
+        let client = SlackClient::new(config(), RetryConfig::default()).unwrap();
+        let (message, event) = sample();
+        let payload = client.build_payload(&message, &event);
+
+        let attachment = &payload["attachments"][0];
+        assert_eq!(payload["text"], "Tool Completed");
+        assert_eq!(payload["username"], "Claude");
+        assert_eq!(attachment["color"], "#e74c3c");
+
+        let blocks = attachment["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["text"]["text"], "Tool Completed");
+        assert_eq!(blocks[1]["text"]["text"], "❌ *Bash* failed\n💻 `cargo test &amp;&amp; echo \"done\"`");
+        assert_eq!(blocks[2]["fields"][0]["text"], "*Tool*\n`Bash`");
+        assert_eq!(blocks[2]["fields"][1]["text"], "*Directory*\n`/work/&lt;repo&gt;`");
+        assert_eq!(blocks[3]["elements"][0]["text"], ":warning:");
+        assert_eq!(blocks[4]["elements"][0]["url"], "https://example.com/session");
+    }
+
+    #[test]
+    fn test_discord_payload() {
+        let client = DiscordClient::new(config(), RetryConfig::default()).unwrap();
+        let (message, event) = sample();
+        let payload = client.build_payload(&message, &event);
+
+        let embed = &payload["embeds"][0];
+        assert_eq!(embed["title"], "Tool Completed");
+        assert_eq!(embed["description"], "❌ **Bash** failed\n💻 `cargo test && echo \"done\"`");
+        assert_eq!(embed["color"], 0xe74c3c);
+        assert_eq!(embed["url"], "https://example.com/session");
+        assert_eq!(embed["fields"][0]["name"], "Tool");
+        assert_eq!(embed["fields"][1]["value"], "`/work/<repo>`");
+        assert_eq!(embed["footer"]["text"], "warning");
+        assert_eq!(payload["allowed_mentions"]["parse"], json!([]));
+    }
+
+    #[test]
+    fn test_missing_fields_and_code_spans() {
+        let event = HookEvent::new("Stop", json!({"cwd": ""}));
+        assert!(hook_fields(&event).is_empty());
+
+        assert_eq!(code_span("a`b"), "`a'b`");
+    }
+}
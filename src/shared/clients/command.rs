@@ -0,0 +1,213 @@
+//! Local command backend
+//!
+//! Runs a program such as `notify-send`, a sound player or a custom script
+//! for each notification. Arguments are rendered from templates and the hook
+//! data is written to the command's stdin as JSON. Commands are killed when
+//! they exceed their timeout, and runs of the same command are limited
+//! across the whole process so that a burst of hooks cannot pile up
+//! processes in the daemon.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+use super::format::{truncate, unescape_html};
+use super::traits::{send_with_retry, ClientStats, HookEvent, NotificationClient, RetryConfig};
+use super::webhook::template_context;
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::CommandBackendConfig;
+use crate::shared::templates::TemplateEngine;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const STDERR_MAX: usize = 500;
+
+/// A command's concurrency limit with the `max_concurrent` it was created for
+type Limit = (usize, Arc<Semaphore>);
+
+/// Concurrency limits shared by every client running the same command
+static LIMITS: LazyLock<Mutex<HashMap<String, Limit>>> = LazyLock::new(Default::default);
+
+/// Client running a local command per notification
+pub struct CommandClient {
+    config: CommandBackendConfig,
+    templates: TemplateEngine,
+    limit: Arc<Semaphore>,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl CommandClient {
+    /// Create a command client, compiling its argument templates
+    pub fn new(config: CommandBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        let mut templates = TemplateEngine::new_for_text();
+        for (i, arg) in config.args.iter().enumerate() {
+            templates.register_template(&arg_template(i), arg)?;
+        }
+
+        // A client configured with a different limit, e.g. after a reload,
+        // replaces the command's limit; runs holding a slot of the old one
+        // finish undisturbed
+        let max_concurrent = config.max_concurrent.max(1);
+        let mut limits = LIMITS.lock().unwrap();
+        let (current, limit) = limits
+            .entry(config.command.clone())
+            .or_insert_with(|| (max_concurrent, Arc::new(Semaphore::new(max_concurrent))));
+        if *current != max_concurrent {
+            *current = max_concurrent;
+            *limit = Arc::new(Semaphore::new(max_concurrent));
+        }
+        let limit = limit.clone();
+        drop(limits);
+
+        Ok(Self {
+            config,
+            templates,
+            limit,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Render the command-line arguments for a notification
+    fn render_args(&self, message: &NtfyMessage, event: &HookEvent) -> Result<Vec<String>> {
+        let mut context = template_context(message, event);
+        context["message"] = Value::String(unescape_html(&message.message));
+
+        (0..self.config.args.len())
+            .map(|i| self.templates.render(&arg_template(i), &context))
+            .collect()
+    }
+
+    /// Run the command once, waiting for a free slot first
+    ///
+    /// The timeout covers both waiting and running; a command still running
+    /// when it elapses is killed.
+    async fn run(&self, args: &[String], stdin: &[u8]) -> Result<()> {
+        let timeout = Duration::from_secs(self.config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+        tokio::time::timeout(timeout, self.run_inner(args, stdin))
+            .await
+            .unwrap_or_else(|_| {
+                anyhow::bail!("'{}' timed out after {}s", self.config.command, timeout.as_secs())
+            })
+    }
+
+    async fn run_inner(&self, args: &[String], stdin: &[u8]) -> Result<()> {
+        let _permit = self.limit.acquire().await.context("Command limiter closed")?;
+
+        let mut child = Command::new(&self.config.command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to run '{}'", self.config.command))?;
+
+        // Commands are free to ignore stdin, so write errors are not failures
+        if let Some(mut pipe) = child.stdin.take() {
+            let input = stdin.to_vec();
+            tokio::spawn(async move {
+                let _ = pipe.write_all(&input).await;
+            });
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .with_context(|| format!("Failed to wait for '{}'", self.config.command))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(
+                "'{}' exited with {}: {}",
+                self.config.command,
+                output.status,
+                truncate(stderr.trim(), STDERR_MAX)
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for CommandClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        self.send_event(message, &HookEvent::default()).await
+    }
+
+    async fn send_event(&self, message: &NtfyMessage, event: &HookEvent) -> Result<()> {
+        let args = self.render_args(message, event)?;
+        let stdin = serde_json::to_vec(&event.data).context("Failed to serialize hook data")?;
+
+        send_with_retry(&self.retry_config, &self.stats, || self.run(&args, &stdin)).await
+    }
+}
+
+fn arg_template(index: usize) -> String {
+    format!("arg:{index}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn client(command: &str, args: &[&str], timeout_secs: u64) -> CommandClient {
+        limited_client(command, args, timeout_secs, 1)
+    }
+
+    fn limited_client(command: &str, args: &[&str], timeout_secs: u64, max_concurrent: usize) -> CommandClient {
+        let config = CommandBackendConfig {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            timeout_secs: Some(timeout_secs),
+            max_concurrent,
+        };
+        CommandClient::new(config, RetryConfig::exponential(0, 0)).unwrap()
+    }
+
+    #[test]
+    fn test_changed_limit_replaces_the_shared_one() {
+        let first = limited_client("test-limit-command", &[], 5, 2);
+        let same = limited_client("test-limit-command", &[], 5, 2);
+        assert!(Arc::ptr_eq(&first.limit, &same.limit));
+
+        let reloaded = limited_client("test-limit-command", &[], 5, 4);
+        assert_eq!(reloaded.limit.available_permits(), 4);
+        assert_eq!(first.limit.available_permits(), 2);
+    }
+
+    #[test]
+    fn test_render_args_unescaped() {
+        let client = client("true", &["{{title}}", "{{message}}", "{{priority}}", "{{hook.tool_name}}"], 5);
+        let message = NtfyMessage {
+            title: Some("Input <needed>".to_string()),
+            message: "Run `a &amp;&amp; b`?".to_string(),
+            priority: Some(4),
+            ..Default::default()
+        };
+        let event = HookEvent::new("Notification", json!({"tool_name": "Bash"}));
+
+        let args = client.render_args(&message, &event).unwrap();
+        assert_eq!(args, vec!["Input <needed>", "Run `a && b`?", "4", "Bash"]);
+    }
+
+    #[tokio::test]
+    async fn test_failure_and_timeout() {
+        let failing = client("sh", &["-c", "echo oops >&2; exit 3"], 5);
+        let error = failing.send(&NtfyMessage::default()).await.unwrap_err();
+        assert!(format!("{error:#}").contains("oops"));
+
+        let slow = client("sleep", &["5"], 1);
+        let started = std::time::Instant::now();
+        let error = slow.send(&NtfyMessage::default()).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
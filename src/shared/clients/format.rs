@@ -0,0 +1,107 @@
+//! Text helpers shared by backends that reformat the rendered message
+
+/// Undo Handlebars' HTML escaping of rendered values
+pub fn unescape_html(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&#x60;", "`")
+        .replace("&#x3D;", "=")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Escape text for use in HTML content and attributes
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape text for HTML, limited to `max` characters once escaped
+///
+/// The text is cut before it is escaped, so that no entity is split, and
+/// the cut is marked with an ellipsis.
+pub fn escape_html_truncated(text: &str, max: usize) -> String {
+    let escaped = escape_html(text);
+    if escaped.chars().count() <= max {
+        return escaped;
+    }
+
+    let mut truncated = String::with_capacity(escaped.len());
+    let mut length = 0;
+    for c in text.chars() {
+        let escaped = escape_html(c.encode_utf8(&mut [0; 4]));
+        let width = escaped.chars().count();
+        if length + width + 1 > max {
+            break;
+        }
+        truncated.push_str(&escaped);
+        length += width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Limit text to `max` characters, marking the cut with an ellipsis
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Convert `**bold**` markdown in HTML-escaped text to `<b>` tags
+pub fn markdown_bold_to_html(text: &str) -> String {
+    let parts: Vec<&str> = text.split("**").collect();
+    let mut html = String::with_capacity(text.len());
+    for (i, part) in parts.iter().enumerate() {
+        html.push_str(part);
+        if i + 1 < parts.len() {
+            // An unbalanced trailing marker is kept literally
+            let closing = i % 2 == 1;
+            let has_partner = closing || i + 2 < parts.len();
+            html.push_str(match (has_partner, closing) {
+                (false, _) => "**",
+                (true, false) => "<b>",
+                (true, true) => "</b>",
+            });
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_html() {
+        assert_eq!(unescape_html("a &amp;&amp; &quot;b&quot; &lt;c&gt;"), "a && \"b\" <c>");
+        assert_eq!(unescape_html("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("abc", 4), "abc");
+        assert_eq!(truncate("ééééé", 3), "éé…");
+    }
+
+    #[test]
+    fn test_escape_html_truncated() {
+        assert_eq!(escape_html_truncated("a & b", 9), "a &amp; b");
+        assert_eq!(escape_html_truncated("a & b", 8), "a &amp;…");
+        assert_eq!(escape_html_truncated("a & b", 7), "a …");
+    }
+
+    #[test]
+    fn test_markdown_bold_to_html() {
+        assert_eq!(markdown_bold_to_html("**a** and **b**"), "<b>a</b> and <b>b</b>");
+        assert_eq!(markdown_bold_to_html("**a** and **b"), "<b>a</b> and **b");
+        assert_eq!(markdown_bold_to_html("plain"), "plain");
+    }
+}
@@ -0,0 +1,31 @@
+//! HTTP helpers shared by the webhook-style backends
+
+use anyhow::{Context, Result};
+use reqwest::{Client, Response};
+use std::time::Duration;
+
+/// User agent sent by every backend
+pub const USER_AGENT: &str = "claude-ntfy/0.1.0";
+
+/// Build an HTTP client with the backend's request timeout (30s by default)
+pub fn build_client(timeout_secs: Option<u64>) -> Result<Client> {
+    Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.unwrap_or(30)))
+        .user_agent(USER_AGENT)
+        .build()
+        .context("Failed to create async HTTP client")
+}
+
+/// Turn a non-success response into an error carrying the response body
+pub async fn ensure_success(response: Response, service: &str) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let error_text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    anyhow::bail!("{} request failed: {} - {}", service, status, error_text)
+}
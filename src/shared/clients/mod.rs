@@ -8,6 +8,14 @@
 //!
 //! - **AsyncNtfyClient**: Primary async-first implementation with advanced features
 //! - **NtfyClient**: Sync wrapper around AsyncNtfyClient for blocking operations  
+//! - **WebhookClient**: Templated JSON POSTs to arbitrary HTTP endpoints
+//! - **SlackClient** / **DiscordClient**: Native chat formatting for incoming webhooks
+//! - **GotifyClient** / **PushoverClient**: Push services with their own priority scales
+//! - **TelegramClient**: Bot messages with inline keyboards, doubling as a reply channel
+//! - **SmtpClient**: Multipart text and HTML email over SMTP
+//! - **CommandClient**: Local programs with templated arguments and the hook JSON on stdin
+//! - **BackendRegistry**: Named backends from `[[backends]]`, fanned out concurrently
+//! - **ClientCache**: Reused clients with a concurrency limit per server
 //! - **Traits**: Clean interfaces with comprehensive error handling
 //!
 //! ## Features
@@ -38,11 +46,21 @@
 //! # }
 //! ```
 
+pub mod chat;
+pub mod command;
+pub mod format;
+pub mod http;
 pub mod ntfy;
+pub mod push;
+pub mod registry;
+pub mod smtp;
+pub mod telegram;
 pub mod traits;
+pub mod webhook;
 
 // Re-export main types for convenience
 pub use ntfy::{AsyncNtfyClient, create_async_client_from_ntfy_config, create_sync_client_from_ntfy_config};
+pub use registry::{BackendRegistry, ClientCache, combine_results};
 
 #[cfg(test)]
 mod tests {
@@ -2,16 +2,16 @@ use async_trait::async_trait;
 use reqwest::{Client, header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}};
 use anyhow::{Context, Result};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
-use tokio::time::sleep;
+use std::time::Duration;
 
-use super::traits::{NotificationClient, ClientStats, RetryConfig};
+use super::traits::{send_with_retry, NotificationClient, ClientStats, RetryConfig};
 use crate::ntfy::NtfyMessage;
 
 /// Configuration for the ntfy client
 #[derive(Debug, Clone)]
 pub struct NtfyClientConfig {
     pub server_url: String,
+    pub topic: Option<String>, // Overrides the message topic when set
     pub auth_token: Option<String>,
     pub timeout_secs: Option<u64>,
     pub retry_config: RetryConfig,
@@ -22,6 +22,7 @@ impl Default for NtfyClientConfig {
     fn default() -> Self {
         Self {
             server_url: "https://ntfy.sh".to_string(),
+            topic: None,
             auth_token: None,
             timeout_secs: Some(30),
             retry_config: RetryConfig::default(),
@@ -72,34 +73,6 @@ impl AsyncNtfyClient {
         NtfyClient::new(self)
     }
     
-    /// Send a notification with built-in retry logic
-    async fn send_with_retry(&self, message: &NtfyMessage) -> Result<()> {
-        let mut last_error = None;
-        
-        for attempt in 0..=self.config.retry_config.max_attempts {
-            match self.send_internal(message).await {
-                Ok(()) => return Ok(()),
-                Err(e) => {
-                    last_error = Some(e);
-                    
-                    if attempt < self.config.retry_config.max_attempts {
-                        // Record retry attempt
-                        if let Ok(mut stats) = self.stats.lock() {
-                            stats.record_retry();
-                        }
-                        
-                        // Calculate delay and wait
-                        let delay = self.config.retry_config.calculate_delay(attempt);
-                        sleep(delay).await;
-                    }
-                }
-            }
-        }
-        
-        // All retries exhausted
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Send failed after retries")))
-    }
-    
     /// Internal send implementation without retry logic
     async fn send_internal(&self, message: &NtfyMessage) -> Result<()> {
         let headers = self.build_headers()?;
@@ -153,8 +126,9 @@ impl AsyncNtfyClient {
     
     /// Build JSON body for the message
     fn build_json_body(&self, message: &NtfyMessage) -> Result<serde_json::Value> {
+        let topic = self.config.topic.as_ref().unwrap_or(&message.topic);
         let mut body = serde_json::json!({
-            "topic": message.topic,
+            "topic": topic,
             "message": message.message,
         });
         
@@ -205,6 +179,31 @@ impl AsyncNtfyClient {
         Ok(body)
     }
     
+    /// Subscribe to a topic's JSON message stream
+    ///
+    /// The subscription is open once this returns, so messages published
+    /// afterwards are guaranteed to be delivered to it. `timeout` bounds the
+    /// lifetime of the whole stream rather than the client's request timeout.
+    pub async fn subscribe(&self, topic: &str, timeout: Duration) -> Result<NtfySubscription> {
+        let url = format!("{}/{}/json", self.config.server_url.trim_end_matches('/'), topic);
+        let response = self.client
+            .get(&url)
+            .headers(self.build_headers()?)
+            .timeout(timeout)
+            .send()
+            .await
+            .context("Failed to subscribe to ntfy topic")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to subscribe to topic {}: {}", topic, response.status());
+        }
+
+        Ok(NtfySubscription {
+            response,
+            buffer: Vec::new(),
+        })
+    }
+    
     /// Simple convenience method for sending basic notifications
     pub async fn send_simple(&self, topic: &str, title: &str, message: &str, priority: u8) -> Result<()> {
         let msg = NtfyMessage {
@@ -230,21 +229,39 @@ impl AsyncNtfyClient {
 #[async_trait]
 impl NotificationClient for AsyncNtfyClient {
     async fn send(&self, message: &NtfyMessage) -> Result<()> {
-        let start = Instant::now();
-        
-        let result = self.send_with_retry(message).await;
-        
-        let elapsed = start.elapsed().as_millis() as u64;
-        
-        // Update statistics
-        if let Ok(mut stats) = self.stats.lock() {
-            match &result {
-                Ok(_) => stats.record_success(elapsed),
-                Err(e) => stats.record_failure(e.to_string()),
+        send_with_retry(&self.config.retry_config, &self.stats, || self.send_internal(message)).await
+    }
+}
+
+/// Open subscription to an ntfy topic's newline-delimited JSON stream
+pub struct NtfySubscription {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+}
+
+impl NtfySubscription {
+    /// Wait for the next published message body
+    ///
+    /// Keepalive and open events are skipped. Returns `Ok(None)` when the
+    /// server closes the stream.
+    pub async fn next_message(&mut self) -> Result<Option<String>> {
+        loop {
+            while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+                let Ok(event) = serde_json::from_slice::<serde_json::Value>(&line) else {
+                    continue;
+                };
+                if event["event"] == "message" {
+                    let message = event["message"].as_str().unwrap_or_default();
+                    return Ok(Some(message.to_string()));
+                }
+            }
+
+            match self.response.chunk().await.context("Failed to read ntfy subscription")? {
+                Some(chunk) => self.buffer.extend_from_slice(&chunk),
+                None => return Ok(None),
             }
         }
-        
-        result
     }
 }
 
@@ -298,11 +315,26 @@ impl From<&crate::shared::config::Config> for NtfyClientConfig {
     }
 }
 
+// Convert from a named ntfy backend
+impl From<&crate::shared::config::NtfyBackendConfig> for NtfyClientConfig {
+    fn from(config: &crate::shared::config::NtfyBackendConfig) -> Self {
+        Self {
+            server_url: config.server_url.clone(),
+            topic: config.topic.clone(),
+            auth_token: config.auth_token.clone(),
+            timeout_secs: config.timeout_secs,
+            retry_config: RetryConfig::default(),
+            user_agent: Some("claude-ntfy/0.1.0".to_string()),
+        }
+    }
+}
+
 // Convert directly from NtfyConfig
 impl From<&crate::shared::config::NtfyConfig> for NtfyClientConfig {
     fn from(config: &crate::shared::config::NtfyConfig) -> Self {
         Self {
             server_url: config.server_url.clone(),
+            topic: None,
             auth_token: config.auth_token.clone(),
             timeout_secs: config.timeout_secs,
             retry_config: RetryConfig::default(),
@@ -315,6 +347,7 @@ impl From<&crate::shared::config::NtfyConfig> for NtfyClientConfig {
 pub fn create_async_client_from_ntfy_config(config: &crate::config::NtfyConfig) -> Result<AsyncNtfyClient> {
     let client_config = NtfyClientConfig {
         server_url: config.server_url.clone(),
+        topic: None,
         auth_token: config.auth_token.clone(),
         timeout_secs: config.timeout_secs,
         retry_config: RetryConfig::default(),
@@ -364,6 +397,24 @@ mod tests {
         assert_eq!(body["markdown"], true);
     }
     
+    #[test]
+    async fn test_topic_override() {
+        let config = NtfyClientConfig {
+            topic: Some("backend-topic".to_string()),
+            ..Default::default()
+        };
+        let client = AsyncNtfyClient::new(config).unwrap();
+        
+        let message = NtfyMessage {
+            topic: "hook-topic".to_string(),
+            message: "Test Message".to_string(),
+            ..Default::default()
+        };
+        
+        let body = client.build_json_body(&message).unwrap();
+        assert_eq!(body["topic"], "backend-topic");
+    }
+    
     #[tokio::test]
     async fn test_retry_config() {
         let config = RetryConfig::exponential(3, 100);
@@ -386,6 +437,13 @@ mod tests {
         assert_eq!(stats.min_latency_ms, 100);
         assert_eq!(stats.max_latency_ms, 200);
         
+        // A true running average, not the mean of the last two
+        stats.record_success(600);
+        assert_eq!(stats.average_latency_ms, 300);
+        assert_eq!(stats.latency_percentile(50.0), Some(200));
+        assert_eq!(stats.latency_percentile(99.0), Some(600));
+        assert_eq!(ClientStats::default().latency_percentile(50.0), None);
+
         stats.record_failure("Test error".to_string());
         assert_eq!(stats.messages_failed, 1);
         // Test that we can track both successes and failures
@@ -0,0 +1,260 @@
+//! Gotify and Pushover backends
+//!
+//! Both services have their own priority scales, so the ntfy priority (1-5)
+//! is mapped onto them: Gotify uses 0-10, Pushover uses -2..2 where 2 is an
+//! emergency that re-alerts until acknowledged or expired.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use super::format::{escape_html_truncated, markdown_bold_to_html, truncate, unescape_html};
+use super::http::{build_client, ensure_success};
+use super::traits::{send_with_retry, ClientStats, NotificationClient, RetryConfig};
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::{GotifyBackendConfig, PushoverBackendConfig};
+
+// Pushover limits
+const PUSHOVER_TITLE_MAX: usize = 250;
+const PUSHOVER_MESSAGE_MAX: usize = 900; // leaves room for <b> tags within 1024
+const PUSHOVER_MIN_RETRY_SECS: u32 = 30;
+const PUSHOVER_MAX_EXPIRE_SECS: u32 = 10800;
+
+/// Gotify client posting to a self-hosted server's message API
+pub struct GotifyClient {
+    client: Client,
+    config: GotifyBackendConfig,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl GotifyClient {
+    pub fn new(config: GotifyBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        Ok(Self {
+            client: build_client(config.timeout_secs)?,
+            config,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Build the Gotify message for a notification
+    fn build_payload(&self, message: &NtfyMessage) -> Value {
+        let mut extras = json!({"client::display": {"contentType": "text/markdown"}});
+        if let Some(click) = &message.click {
+            extras["client::notification"] = json!({"click": {"url": click}});
+        }
+
+        let mut payload = json!({
+            "message": unescape_html(&message.message),
+            "priority": gotify_priority(message.priority),
+            "extras": extras,
+        });
+        if let Some(title) = &message.title {
+            payload["title"] = json!(title);
+        }
+        payload
+    }
+
+    async fn send_internal(&self, payload: &Value) -> Result<()> {
+        let url = format!("{}/message", self.config.server_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .post(&url)
+            .header("X-Gotify-Key", &self.config.app_token)
+            .json(payload)
+            .send()
+            .await
+            .context("Failed to send Gotify message")?;
+
+        ensure_success(response, "Gotify").await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for GotifyClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        let payload = self.build_payload(message);
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&payload)).await
+    }
+}
+
+/// Pushover client using the messages API
+pub struct PushoverClient {
+    client: Client,
+    config: PushoverBackendConfig,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl PushoverClient {
+    pub fn new(config: PushoverBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        Ok(Self {
+            client: build_client(config.timeout_secs)?,
+            config,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Build the form parameters for a notification
+    fn build_form(&self, message: &NtfyMessage) -> Vec<(&'static str, String)> {
+        let priority = pushover_priority(message.priority);
+        let mut form = vec![
+            ("token", self.config.app_token.clone()),
+            ("user", self.config.user_key.clone()),
+            ("message", pushover_html(&message.message)),
+            ("html", "1".to_string()),
+            ("priority", priority.to_string()),
+        ];
+
+        if let Some(title) = &message.title {
+            form.push(("title", truncate(title, PUSHOVER_TITLE_MAX)));
+        }
+        if let Some(device) = &self.config.device {
+            form.push(("device", device.clone()));
+        }
+        if let Some(click) = &message.click {
+            form.push(("url", click.clone()));
+            form.push(("url_title", "Open".to_string()));
+        }
+        if priority == 2 {
+            let retry = self.config.emergency_retry_secs.max(PUSHOVER_MIN_RETRY_SECS);
+            let expire = self.config.emergency_expire_secs.min(PUSHOVER_MAX_EXPIRE_SECS);
+            form.push(("retry", retry.to_string()));
+            form.push(("expire", expire.to_string()));
+        }
+
+        form
+    }
+
+    async fn send_internal(&self, form: &[(&'static str, String)]) -> Result<()> {
+        let url = format!("{}/1/messages.json", self.config.api_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .post(&url)
+            .form(form)
+            .send()
+            .await
+            .context("Failed to send Pushover message")?;
+
+        ensure_success(response, "Pushover").await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for PushoverClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        let form = self.build_form(message);
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&form)).await
+    }
+}
+
+/// Map an ntfy priority (1-5) onto Gotify's 0-10 scale
+fn gotify_priority(priority: Option<u8>) -> u8 {
+    match priority.unwrap_or(3) {
+        1 => 1, // still listed, but silent
+        2 => 3,
+        4 => 8,
+        5 => 10,
+        _ => 5,
+    }
+}
+
+/// Convert the rendered body, which is HTML-escaped, to a Pushover HTML message
+///
+/// The limit is applied to the unescaped text, so the cut never splits an entity.
+fn pushover_html(rendered: &str) -> String {
+    markdown_bold_to_html(&escape_html_truncated(&unescape_html(rendered), PUSHOVER_MESSAGE_MAX))
+}
+
+/// Map an ntfy priority (1-5) onto Pushover's -2..2 scale
+fn pushover_priority(priority: Option<u8>) -> i8 {
+    match priority.unwrap_or(3) {
+        1 => -2,
+        2 => -1,
+        4 => 1,
+        5 => 2,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(priority: u8) -> NtfyMessage {
+        NtfyMessage {
+            title: Some("Approval needed: Bash".to_string()),
+            message: "💻 **Bash** wants to run `rm -rf build &amp;&amp; make`".to_string(),
+            priority: Some(priority),
+            click: Some("https://example.com".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn pushover(retry: u32, expire: u32) -> PushoverClient {
+        let config = PushoverBackendConfig {
+            app_token: "app".to_string(),
+            user_key: "user".to_string(),
+            device: None,
+            api_url: "http://127.0.0.1:9".to_string(),
+            emergency_retry_secs: retry,
+            emergency_expire_secs: expire,
+            timeout_secs: Some(5),
+        };
+        PushoverClient::new(config, RetryConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_priority_mapping() {
+        let gotify: Vec<u8> = (1..=5).map(|p| gotify_priority(Some(p))).collect();
+        assert_eq!(gotify, vec![1, 3, 5, 8, 10]);
+
+        let pushover: Vec<i8> = (1..=5).map(|p| pushover_priority(Some(p))).collect();
+        assert_eq!(pushover, vec![-2, -1, 0, 1, 2]);
+        assert_eq!(pushover_priority(None), 0);
+    }
+
+    #[test]
+    fn test_gotify_payload() {
+        let config = GotifyBackendConfig {
+            server_url: "http://127.0.0.1:9".to_string(),
+            app_token: "token".to_string(),
+            timeout_secs: None,
+        };
+        let client = GotifyClient::new(config, RetryConfig::default()).unwrap();
+        let payload = client.build_payload(&message(4));
+
+        assert_eq!(payload["priority"], 8);
+        assert_eq!(payload["message"], "💻 **Bash** wants to run `rm -rf build && make`");
+        assert_eq!(payload["extras"]["client::notification"]["click"]["url"], "https://example.com");
+    }
+
+    #[test]
+    fn test_pushover_message_is_cut_between_entities() {
+        let html = pushover_html(&"&amp;".repeat(400));
+
+        assert!(html.chars().count() <= PUSHOVER_MESSAGE_MAX);
+        assert!(html.ends_with("&amp;…"), "{html}");
+    }
+
+    #[test]
+    fn test_pushover_emergency_parameters() {
+        let form = pushover(10, 100_000).build_form(&message(5));
+        let get = |key: &str| form.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str());
+
+        assert_eq!(get("priority"), Some("2"));
+        assert_eq!(get("retry"), Some("30"));
+        assert_eq!(get("expire"), Some("10800"));
+        assert_eq!(get("url"), Some("https://example.com"));
+        assert_eq!(get("message"), Some("💻 <b>Bash</b> wants to run `rm -rf build &amp;&amp; make`"));
+
+        let form = pushover(60, 3600).build_form(&message(3));
+        assert!(!form.iter().any(|(k, _)| *k == "retry" || *k == "expire"));
+    }
+}
@@ -0,0 +1,396 @@
+//! Notification backend registry
+//!
+//! Builds a [`NotificationClient`] for each configured backend and fans a
+//! rendered message out to the backends a hook is routed to. Backends are
+//! delivered to concurrently, and each backend's result is reported
+//! separately so that callers can retry only the ones that failed.
+//!
+//! Long-running processes such as the daemon keep clients in a
+//! [`ClientCache`], so that connections are reused between notifications
+//! and no single server is sent more than a few requests at once.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::traits::{send_with_retry, ClientStats, HookEvent, NotificationClient, RetryConfig};
+use super::ntfy::NtfyClientConfig;
+use super::chat::{DiscordClient, SlackClient};
+use super::command::CommandClient;
+use super::push::{GotifyClient, PushoverClient};
+use super::smtp::SmtpClient;
+use super::telegram::TelegramClient;
+use super::webhook::WebhookClient;
+use super::AsyncNtfyClient;
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::{BackendConfig, BackendKind};
+
+/// Create the notification client for a backend configuration
+pub fn create_client(config: &BackendConfig, retry_config: &RetryConfig) -> Result<Arc<dyn NotificationClient>> {
+    let client: Arc<dyn NotificationClient> = match &config.kind {
+        BackendKind::Ntfy(ntfy) => {
+            let mut client_config = NtfyClientConfig::from(ntfy);
+            client_config.retry_config = retry_config.clone();
+            Arc::new(AsyncNtfyClient::new(client_config)?)
+        }
+        BackendKind::Webhook(webhook) => Arc::new(WebhookClient::new(webhook.clone(), retry_config.clone())?),
+        BackendKind::Slack(slack) => Arc::new(SlackClient::new(slack.clone(), retry_config.clone())?),
+        BackendKind::Discord(discord) => Arc::new(DiscordClient::new(discord.clone(), retry_config.clone())?),
+        BackendKind::Gotify(gotify) => Arc::new(GotifyClient::new(gotify.clone(), retry_config.clone())?),
+        BackendKind::Pushover(pushover) => Arc::new(PushoverClient::new(pushover.clone(), retry_config.clone())?),
+        BackendKind::Telegram(telegram) => Arc::new(TelegramClient::new(telegram.clone(), retry_config.clone())?),
+        BackendKind::Smtp(smtp) => Arc::new(SmtpClient::new(smtp.clone(), retry_config.clone())?),
+        BackendKind::Command(command) => Arc::new(CommandClient::new(command.clone(), retry_config.clone())?),
+    };
+    Ok(client)
+}
+
+/// Clients shared between deliveries, with a concurrency limit per server
+///
+/// Clients are keyed by their full backend settings, including credentials,
+/// so backends that differ only in name share one client and its connection
+/// pool.
+pub struct ClientCache {
+    /// Clients by backend settings and retry policy
+    clients: Mutex<HashMap<(String, String), Arc<dyn NotificationClient>>>,
+    servers: Mutex<HashMap<String, Arc<Semaphore>>>,
+    max_per_server: usize,
+}
+
+impl ClientCache {
+    /// Create an empty cache allowing `max_per_server` concurrent sends per host
+    pub fn new(max_per_server: usize) -> Self {
+        Self {
+            clients: Mutex::new(HashMap::new()),
+            servers: Mutex::new(HashMap::new()),
+            max_per_server: max_per_server.max(1),
+        }
+    }
+
+    /// Build a registry for the backend configurations, reusing cached clients
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the backend whose client could not be created.
+    pub fn registry(&self, configs: &[BackendConfig], retry_config: &RetryConfig) -> Result<BackendRegistry> {
+        let clients = configs
+            .iter()
+            .map(|config| {
+                self.client(config, retry_config)
+                    .with_context(|| format!("Failed to create {} backend '{}'", config.kind.type_name(), config.name))
+                    .map(|client| (config.name.clone(), client))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(BackendRegistry { clients })
+    }
+
+    /// Drop cached clients whose settings match none of `backends`, returning how many
+    ///
+    /// Deliveries in progress finish with the clients they already hold.
+    pub fn retain(&self, backends: &[BackendConfig]) -> usize {
+        let live: HashSet<String> = backends
+            .iter()
+            .filter_map(|backend| serde_json::to_string(&backend.kind).ok())
+            .collect();
+        let mut clients = self.clients.lock().unwrap();
+        let before = clients.len();
+        clients.retain(|(settings, _), _| live.contains(settings));
+        before - clients.len()
+    }
+
+    fn client(&self, config: &BackendConfig, retry_config: &RetryConfig) -> Result<Arc<dyn NotificationClient>> {
+        let key = (
+            serde_json::to_string(&config.kind).context("Failed to serialize backend")?,
+            format!("{retry_config:?}"),
+        );
+        if let Some(client) = self.clients.lock().unwrap().get(&key) {
+            return Ok(client.clone());
+        }
+
+        let client = match config.kind.server() {
+            Some(server) => Arc::new(LimitedClient::new(
+                config,
+                retry_config,
+                self.server_limit(server),
+            )?),
+            None => create_client(config, retry_config)?,
+        };
+
+        // A concurrent delivery may have created the same client meanwhile
+        Ok(self.clients.lock().unwrap().entry(key).or_insert(client).clone())
+    }
+
+    fn server_limit(&self, server: String) -> Arc<Semaphore> {
+        self.servers
+            .lock()
+            .unwrap()
+            .entry(server)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_server)))
+            .clone()
+    }
+}
+
+/// Client that waits for a free slot on its server before each send attempt
+///
+/// It retries sends itself rather than leaving that to the inner client, so
+/// the slot is given up while waiting to retry.
+struct LimitedClient {
+    inner: Arc<dyn NotificationClient>,
+    limit: Arc<Semaphore>,
+    retry_config: RetryConfig,
+    stats: Mutex<ClientStats>,
+}
+
+impl LimitedClient {
+    fn new(config: &BackendConfig, retry_config: &RetryConfig, limit: Arc<Semaphore>) -> Result<Self> {
+        let single_attempt = RetryConfig {
+            max_attempts: 0,
+            ..retry_config.clone()
+        };
+        Ok(Self {
+            inner: create_client(config, &single_attempt)?,
+            limit,
+            retry_config: retry_config.clone(),
+            stats: Mutex::new(ClientStats::default()),
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationClient for LimitedClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        send_with_retry(&self.retry_config, &self.stats, || async {
+            let _permit = self.limit.acquire().await.context("Server limiter closed")?;
+            self.inner.send(message).await
+        })
+        .await
+    }
+
+    async fn send_event(&self, message: &NtfyMessage, event: &HookEvent) -> Result<()> {
+        send_with_retry(&self.retry_config, &self.stats, || async {
+            let _permit = self.limit.acquire().await.context("Server limiter closed")?;
+            self.inner.send_event(message, event).await
+        })
+        .await
+    }
+}
+
+/// Delivery outcome for a single backend
+#[derive(Debug)]
+pub struct BackendResult {
+    pub backend: String,
+    pub result: Result<()>,
+    /// Time taken including client retries and waiting for a server slot
+    pub latency: Duration,
+}
+
+/// Named notification clients, in routing order
+#[derive(Clone, Default)]
+pub struct BackendRegistry {
+    clients: Vec<(String, Arc<dyn NotificationClient>)>,
+}
+
+impl BackendRegistry {
+    /// Build clients for every backend configuration
+    ///
+    /// `retry_config` is the per-send retry policy used by every client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the backend whose client could not be created.
+    pub fn from_configs(configs: &[BackendConfig], retry_config: &RetryConfig) -> Result<Self> {
+        let clients = configs
+            .iter()
+            .map(|config| {
+                create_client(config, retry_config)
+                    .with_context(|| format!("Failed to create {} backend '{}'", config.kind.type_name(), config.name))
+                    .map(|client| (config.name.clone(), client))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { clients })
+    }
+
+    /// Names of the registered backends
+    pub fn names(&self) -> Vec<String> {
+        self.clients.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Send a message for a hook event to every registered backend
+    pub async fn send(&self, message: &NtfyMessage, event: &HookEvent) -> Vec<BackendResult> {
+        self.send_to(&self.names(), message, event).await
+    }
+
+    /// Send a message for a hook event to the named backends concurrently
+    ///
+    /// Results are returned in registry order. Names that are not
+    /// registered are ignored.
+    pub async fn send_to(&self, names: &[String], message: &NtfyMessage, event: &HookEvent) -> Vec<BackendResult> {
+        let mut deliveries = JoinSet::new();
+        for (index, (name, client)) in self.clients.iter().enumerate() {
+            if !names.contains(name) {
+                continue;
+            }
+            let client = client.clone();
+            let message = message.clone();
+            let event = event.clone();
+            let name = name.clone();
+            deliveries.spawn(async move {
+                let started = Instant::now();
+                let result = client.send_event(&message, &event).await;
+                let latency = started.elapsed();
+                (index, BackendResult { backend: name, result, latency })
+            });
+        }
+
+        let mut results = Vec::with_capacity(deliveries.len());
+        while let Some(joined) = deliveries.join_next().await {
+            match joined {
+                Ok(result) => results.push(result),
+                Err(e) => tracing::error!("Backend delivery task failed: {}", e),
+            }
+        }
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
+
+/// Combine per-backend results into a single result
+///
+/// Succeeds only if every backend succeeded; otherwise the error lists each
+/// failed backend with its cause.
+pub fn combine_results(results: Vec<BackendResult>) -> Result<()> {
+    let failures: Vec<String> = results
+        .into_iter()
+        .filter_map(|r| r.result.err().map(|e| format!("{}: {:#}", r.backend, e)))
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        anyhow::bail!("Failed to deliver to {}", failures.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::config::NtfyBackendConfig;
+
+    struct FailingClient;
+
+    #[async_trait]
+    impl NotificationClient for FailingClient {
+        async fn send(&self, _message: &NtfyMessage) -> Result<()> {
+            anyhow::bail!("connection refused")
+        }
+    }
+
+    struct OkClient;
+
+    #[async_trait]
+    impl NotificationClient for OkClient {
+        async fn send(&self, _message: &NtfyMessage) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn ntfy_backend(name: &str) -> BackendConfig {
+        BackendConfig {
+            name: name.to_string(),
+            kind: BackendKind::Ntfy(NtfyBackendConfig {
+                server_url: "https://ntfy.example.com".to_string(),
+                topic: None,
+                auth_token: None,
+                timeout_secs: Some(5),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_registry_from_configs() {
+        let registry = BackendRegistry::from_configs(
+            &[ntfy_backend("phone"), ntfy_backend("desk")],
+            &RetryConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(registry.names(), vec!["phone", "desk"]);
+    }
+
+    #[test]
+    fn test_cache_reuses_clients_and_limits_servers() {
+        let cache = ClientCache::new(2);
+        let retry = RetryConfig::default();
+        let first = cache.registry(&[ntfy_backend("phone"), ntfy_backend("desk")], &retry).unwrap();
+        let second = cache.registry(&[ntfy_backend("phone")], &retry).unwrap();
+
+        // Same settings under different names share one client
+        assert_eq!(cache.clients.lock().unwrap().len(), 1);
+        assert!(Arc::ptr_eq(&first.clients[0].1, &second.clients[0].1));
+        assert_eq!(cache.servers.lock().unwrap()["ntfy.example.com"].available_permits(), 2);
+
+        let mut other = ntfy_backend("phone");
+        if let BackendKind::Ntfy(ntfy) = &mut other.kind {
+            ntfy.auth_token = Some("secret".to_string());
+        }
+        cache.registry(&[other.clone()], &retry).unwrap();
+        assert_eq!(cache.clients.lock().unwrap().len(), 2);
+        assert_eq!(cache.servers.lock().unwrap().len(), 1);
+
+        // Only clients no configuration uses any more are dropped
+        assert_eq!(cache.retain(&[other.clone()]), 1);
+        assert_eq!(cache.retain(&[other]), 0);
+        assert_eq!(cache.clients.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_limited_client_frees_its_slot_between_retries() {
+        let limit = Arc::new(Semaphore::new(1));
+        let client = Arc::new(LimitedClient {
+            inner: Arc::new(FailingClient),
+            limit: limit.clone(),
+            retry_config: RetryConfig::exponential(1, 500),
+            stats: Mutex::new(ClientStats::default()),
+        });
+
+        let sending = tokio::spawn({
+            let client = client.clone();
+            async move { client.send(&NtfyMessage::default()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(limit.available_permits(), 1);
+
+        assert!(sending.await.unwrap().is_err());
+        assert_eq!(client.stats.lock().unwrap().messages_failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_reports_each_backend() {
+        let registry = BackendRegistry {
+            clients: vec![
+                ("ok".to_string(), Arc::new(OkClient)),
+                ("broken".to_string(), Arc::new(FailingClient)),
+            ],
+        };
+
+        let results = registry.send(&NtfyMessage::default(), &HookEvent::default()).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].backend, "ok");
+        assert!(results[0].result.is_ok());
+        assert!(results[1].result.is_err());
+
+        let error = combine_results(results).unwrap_err().to_string();
+        assert!(error.contains("broken: connection refused"));
+        assert!(!error.contains("ok:"));
+
+        let results = registry
+            .send_to(&["ok".to_string()], &NtfyMessage::default(), &HookEvent::default())
+            .await;
+        assert!(combine_results(results).is_ok());
+    }
+}
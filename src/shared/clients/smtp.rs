@@ -0,0 +1,244 @@
+//! SMTP email backend
+//!
+//! Sends each notification as a multipart/alternative email with a plain
+//! text and an HTML rendering of the templated message. Urgent and low
+//! priorities are carried in the `X-Priority` header so that mail clients
+//! can flag or file them.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use lettre::message::header::{Header, HeaderName, HeaderValue};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::format::{escape_html, markdown_bold_to_html, unescape_html};
+use super::traits::{send_with_retry, ClientStats, HookEvent, NotificationClient, RetryConfig};
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::{SmtpBackendConfig, SmtpSecurity};
+
+/// SMTP client sending to a fixed list of recipients
+pub struct SmtpClient {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl SmtpClient {
+    pub fn new(config: SmtpBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        let from: Mailbox = config
+            .from
+            .parse()
+            .with_context(|| format!("Invalid sender address '{}'", config.from))?;
+        let to = config
+            .to
+            .iter()
+            .map(|address| {
+                address
+                    .parse::<Mailbox>()
+                    .with_context(|| format!("Invalid recipient address '{address}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if to.is_empty() {
+            anyhow::bail!("At least one recipient is required");
+        }
+
+        let mut builder = match config.security {
+            SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .context("Failed to configure STARTTLS")?,
+            SmtpSecurity::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).context("Failed to configure TLS")?
+            }
+            SmtpSecurity::Plain => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+        builder = builder
+            .port(config.port.unwrap_or(config.security.default_port()))
+            .timeout(Some(Duration::from_secs(config.timeout_secs.unwrap_or(30))));
+        if let Some(username) = &config.username {
+            let password = config.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Build the email for a notification
+    fn build_email(&self, message: &NtfyMessage, event: &HookEvent) -> Result<Message> {
+        let subject = message
+            .title
+            .clone()
+            .or_else(|| (!event.hook_name.is_empty()).then(|| event.hook_name.clone()))
+            .unwrap_or_else(|| "Claude Code".to_string());
+
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject.clone());
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        if let Some(priority) = message.priority.and_then(x_priority) {
+            builder = builder.header(XPriority(priority));
+        }
+
+        builder
+            .multipart(MultiPart::alternative_plain_html(
+                text_body(message),
+                html_body(&subject, message),
+            ))
+            .context("Failed to build email")
+    }
+
+    async fn send_internal(&self, email: &Message) -> Result<()> {
+        self.transport
+            .send(email.clone())
+            .await
+            .context("Failed to send email")?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for SmtpClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        self.send_event(message, &HookEvent::default()).await
+    }
+
+    async fn send_event(&self, message: &NtfyMessage, event: &HookEvent) -> Result<()> {
+        let email = self.build_email(message, event)?;
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&email)).await
+    }
+}
+
+/// `X-Priority` header value, where 1 is highest and 5 lowest
+#[derive(Clone)]
+struct XPriority(u8);
+
+impl Header for XPriority {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("X-Priority")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(s.trim().get(..1).unwrap_or_default().parse()?))
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), self.0.to_string())
+    }
+}
+
+/// Map an ntfy priority (1-5) onto `X-Priority`; the default is left unset
+fn x_priority(priority: u8) -> Option<u8> {
+    match priority {
+        1 => Some(5),
+        2 => Some(4),
+        4 => Some(2),
+        5 => Some(1),
+        _ => None,
+    }
+}
+
+fn text_body(message: &NtfyMessage) -> String {
+    let mut text = unescape_html(&message.message);
+    if let Some(click) = &message.click {
+        text.push_str(&format!("\n\n{click}"));
+    }
+    text
+}
+
+/// Render the HTML-escaped markdown body as an HTML document
+fn html_body(subject: &str, message: &NtfyMessage) -> String {
+    let mut html = format!(
+        "<html><body style=\"font-family: sans-serif\">\n<h2>{}</h2>\n<p>{}</p>\n",
+        escape_html(subject),
+        markdown_to_html(&message.message).replace('\n', "<br>\n")
+    );
+    if let Some(click) = &message.click {
+        html.push_str(&format!("<p><a href=\"{0}\">{0}</a></p>\n", escape_html(click)));
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Convert `**bold**` and `` `code` `` in HTML-escaped markdown to tags
+fn markdown_to_html(text: &str) -> String {
+    let html = markdown_bold_to_html(text);
+    let parts: Vec<&str> = html.split('`').collect();
+    if parts.len().is_multiple_of(2) {
+        // Unbalanced backticks are kept literally
+        return html;
+    }
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| match i % 2 {
+            1 => format!("<code>{part}</code>"),
+            _ => part.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(to: Vec<&str>) -> SmtpBackendConfig {
+        SmtpBackendConfig {
+            host: "127.0.0.1".to_string(),
+            port: Some(2525),
+            security: SmtpSecurity::Plain,
+            username: None,
+            password: None,
+            from: "Claude <claude@example.com>".to_string(),
+            to: to.into_iter().map(str::to_string).collect(),
+            timeout_secs: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_email_is_multipart() {
+        let client = SmtpClient::new(config(vec!["dev@example.com", "ops@example.com"]), RetryConfig::default()).unwrap();
+        let message = NtfyMessage {
+            title: Some("Session <done>".to_string()),
+            message: "✅ **Bash** ran `make &amp;&amp; make test`".to_string(),
+            priority: Some(5),
+            click: Some("https://example.com/s?a=1&b=2".to_string()),
+            ..Default::default()
+        };
+
+        let email = client.build_email(&message, &HookEvent::default()).unwrap();
+        let raw = String::from_utf8(email.formatted()).unwrap();
+        assert!(raw.contains("To: dev@example.com, ops@example.com"));
+        assert!(raw.contains("Subject: Session <done>"));
+        assert!(raw.contains("X-Priority: 1"));
+        assert!(raw.contains("multipart/alternative"));
+        assert!(raw.contains("text/plain"));
+        assert!(raw.contains("text/html"));
+
+        assert_eq!(text_body(&message), "✅ **Bash** ran `make && make test`\n\nhttps://example.com/s?a=1&b=2");
+        let html = html_body("Session <done>", &message);
+        assert!(html.contains("<h2>Session &lt;done&gt;</h2>"));
+        assert!(html.contains("<b>Bash</b> ran <code>make &amp;&amp; make test</code>"));
+        assert!(html.contains("href=\"https://example.com/s?a=1&amp;b=2\""));
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        assert!(SmtpClient::new(config(vec![]), RetryConfig::default()).is_err());
+        assert!(SmtpClient::new(config(vec!["not an address"]), RetryConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        assert_eq!(markdown_to_html("a `b` **c**"), "a <code>b</code> <b>c</b>");
+        assert_eq!(markdown_to_html("it`s"), "it`s");
+    }
+}
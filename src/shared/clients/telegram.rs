@@ -0,0 +1,523 @@
+//! Telegram bot backend
+//!
+//! Sends notifications as MarkdownV2 messages to a chat, with ntfy actions
+//! mapped to inline keyboard buttons: `view` actions become URL buttons and
+//! `http` actions become callback buttons carrying the action body. Replies
+//! (button presses and text messages in the chat) are read back with
+//! `getUpdates` long polling, so a bot can also answer decision hooks.
+//!
+//! Updates are handed to whoever polls for them, and Telegram refuses a second
+//! poller while one is waiting, so only one hook at a time can wait for a
+//! reply from a bot. Others fail right away instead of stealing its updates.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::format::unescape_html;
+use super::http::{build_client, ensure_success};
+use super::traits::{send_with_retry, ClientStats, NotificationClient, RetryConfig};
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::TelegramBackendConfig;
+
+/// Characters that must be escaped outside code spans in MarkdownV2
+const MARKDOWN_V2_SPECIAL: &str = "_*[]()~`>#+-=|{}.!\\";
+
+const MESSAGE_MAX: usize = 4096;
+const CALLBACK_DATA_MAX: usize = 64;
+const LONG_POLL_SECS: u64 = 25;
+
+/// Telegram Bot API client
+pub struct TelegramClient {
+    client: Client,
+    config: TelegramBackendConfig,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl TelegramClient {
+    pub fn new(config: TelegramBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        Ok(Self {
+            client: build_client(config.timeout_secs)?,
+            config,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Send a message and wait for the first reply accepted by `matcher`
+    ///
+    /// Replies are callback data from button presses and text messages sent
+    /// to the configured chat. Returns `Ok(None)` if nothing matching arrives
+    /// before the timeout.
+    ///
+    /// # Errors
+    ///
+    /// Fails without sending if another process or task is already waiting
+    /// for a reply from the same bot.
+    pub async fn send_and_wait<T>(
+        &self,
+        message: &NtfyMessage,
+        timeout: Duration,
+        mut matcher: impl FnMut(&str) -> Option<T>,
+    ) -> Result<Option<T>> {
+        ensure_callbacks_fit(message)?;
+        let base_dirs = directories::BaseDirs::new().context("Failed to get base directories")?;
+        let _waiting = lock_updates(&base_dirs.home_dir().join(".claude/ntfy-service"), &self.config.bot_token)?;
+
+        // Skip updates that arrived before this message was sent
+        let mut offset = match self.get_updates(-1, 0).await?.last() {
+            Some(update) => update_id(update) + 1,
+            None => 0,
+        };
+
+        self.send(message).await.context("Failed to send Telegram message")?;
+
+        let wait = async {
+            loop {
+                for update in self.get_updates(offset, LONG_POLL_SECS).await? {
+                    offset = update_id(&update) + 1;
+                    let Some((reply, callback_id)) = self.reply_from_update(&update) else {
+                        continue;
+                    };
+                    if let Some(callback_id) = callback_id {
+                        self.answer_callback(&callback_id).await;
+                    }
+                    if let Some(value) = matcher(&reply) {
+                        return Ok(Some(value));
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.unwrap_or(Ok(None))
+    }
+
+    /// Build the sendMessage request for a notification
+    fn build_payload(&self, message: &NtfyMessage) -> Value {
+        let body = to_markdown_v2(&message.message);
+        let text = match &message.title {
+            Some(title) => format!("*{}*\n\n{}", escape_markdown_v2(title), body),
+            None => body,
+        };
+        let text = truncate_markdown_v2(&text, MESSAGE_MAX);
+
+        let mut payload = json!({
+            "chat_id": self.config.chat_id,
+            "text": text,
+            "parse_mode": "MarkdownV2",
+            "disable_notification": message.priority.is_some_and(|p| p <= 2),
+        });
+        if let Some(keyboard) = inline_keyboard(message) {
+            payload["reply_markup"] = json!({"inline_keyboard": [keyboard]});
+        }
+        payload
+    }
+
+    /// Extract the reply text from an update sent to our chat
+    ///
+    /// Returns the text and, for button presses, the callback query id.
+    fn reply_from_update(&self, update: &Value) -> Option<(String, Option<String>)> {
+        if let Some(callback) = update.get("callback_query") {
+            if !self.is_our_chat(&callback["message"]["chat"]) {
+                return None;
+            }
+            let data = callback["data"].as_str()?.to_string();
+            return Some((data, callback["id"].as_str().map(str::to_string)));
+        }
+
+        let message = update.get("message")?;
+        if !self.is_our_chat(&message["chat"]) {
+            return None;
+        }
+        Some((message["text"].as_str()?.to_string(), None))
+    }
+
+    fn is_our_chat(&self, chat: &Value) -> bool {
+        let chat_id = &self.config.chat_id;
+        chat["id"].as_i64().is_some_and(|id| &id.to_string() == chat_id)
+            || chat["username"].as_str().is_some_and(|name| chat_id.strip_prefix('@') == Some(name))
+    }
+
+    /// Long-poll for updates after `offset`
+    async fn get_updates(&self, offset: i64, poll_secs: u64) -> Result<Vec<Value>> {
+        let response = self
+            .client
+            .post(self.method_url("getUpdates"))
+            .json(&json!({
+                "offset": offset,
+                "timeout": poll_secs,
+                "allowed_updates": ["message", "callback_query"],
+            }))
+            .timeout(Duration::from_secs(poll_secs + 10))
+            .send()
+            .await
+            .map_err(|e| e.without_url())
+            .context("Failed to poll Telegram updates")?;
+
+        let body: Value = ensure_success(response, "Telegram")
+            .await?
+            .json()
+            .await
+            .context("Invalid Telegram getUpdates response")?;
+        Ok(body["result"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Stop the button's loading spinner; failures only affect the UI
+    async fn answer_callback(&self, callback_id: &str) {
+        let result = self
+            .client
+            .post(self.method_url("answerCallbackQuery"))
+            .json(&json!({"callback_query_id": callback_id, "text": "Received"}))
+            .send()
+            .await;
+        if let Err(e) = result {
+            tracing::debug!("Failed to answer Telegram callback: {}", e.without_url());
+        }
+    }
+
+    async fn send_internal(&self, payload: &Value) -> Result<()> {
+        let response = self
+            .client
+            .post(self.method_url("sendMessage"))
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| e.without_url())
+            .context("Failed to send Telegram message")?;
+
+        ensure_success(response, "Telegram").await?;
+        Ok(())
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!(
+            "{}/bot{}/{}",
+            self.config.api_url.trim_end_matches('/'),
+            self.config.bot_token,
+            method
+        )
+    }
+}
+
+#[async_trait]
+impl NotificationClient for TelegramClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        let payload = self.build_payload(message);
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&payload)).await
+    }
+}
+
+fn update_id(update: &Value) -> i64 {
+    update["update_id"].as_i64().unwrap_or_default()
+}
+
+/// Map ntfy actions (and `click`) onto a row of inline keyboard buttons
+fn inline_keyboard(message: &NtfyMessage) -> Option<Vec<Value>> {
+    let mut buttons: Vec<Value> = message
+        .actions
+        .iter()
+        .flatten()
+        .filter_map(|action| match action.action.as_str() {
+            "view" => action
+                .url
+                .as_ref()
+                .map(|url| json!({"text": action.label, "url": url})),
+            "http" => action
+                .body
+                .as_ref()
+                .map(|body| json!({"text": action.label, "callback_data": callback_data(body)})),
+            _ => None,
+        })
+        .collect();
+
+    if let Some(click) = &message.click {
+        buttons.push(json!({"text": "Open", "url": click}));
+    }
+
+    (!buttons.is_empty()).then_some(buttons)
+}
+
+/// Escape text so MarkdownV2 shows it literally
+fn escape_markdown_v2(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN_V2_SPECIAL.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Convert a rendered markdown body to Telegram MarkdownV2
+///
+/// `**bold**` and `` `code` `` are kept; everything else is escaped. If the
+/// markers are unbalanced the whole text is escaped, since Telegram rejects
+/// malformed entities.
+fn to_markdown_v2(markdown: &str) -> String {
+    let text = unescape_html(markdown);
+    let balanced = text.matches("**").count().is_multiple_of(2) && text.matches('`').count().is_multiple_of(2);
+    if !balanced {
+        return escape_markdown_v2(&text);
+    }
+
+    let mut converted = String::with_capacity(text.len());
+    let mut in_code = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => {
+                in_code = !in_code;
+                converted.push('`');
+            }
+            '\\' if in_code => converted.push_str("\\\\"),
+            _ if in_code => converted.push(c),
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                converted.push('*');
+            }
+            _ if MARKDOWN_V2_SPECIAL.contains(c) => {
+                converted.push('\\');
+                converted.push(c);
+            }
+            _ => converted.push(c),
+        }
+    }
+    converted
+}
+
+/// Limit MarkdownV2 text to `max` characters
+///
+/// The cut never splits an escape sequence, and bold or code entities left
+/// open by it are closed, since Telegram rejects malformed entities.
+fn truncate_markdown_v2(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+
+    let mut truncated = String::with_capacity(text.len());
+    let (mut length, mut in_code, mut in_bold, mut opened) = (0, false, false, false);
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        let mut token = String::from(c);
+        if c == '\\' {
+            token.extend(chars.next());
+        }
+        let (code, bold) = match c {
+            '`' => (!in_code, in_bold),
+            '*' if !in_code => (in_code, !in_bold),
+            _ => (in_code, in_bold),
+        };
+
+        // Leave room for the markers closing open entities and the ellipsis
+        let width = token.chars().count();
+        if length + width + usize::from(code) + usize::from(bold) + 1 > max {
+            break;
+        }
+        truncated.push_str(&token);
+        length += width;
+        opened = (code && !in_code) || (bold && !in_bold);
+        (in_code, in_bold) = (code, bold);
+    }
+
+    // Drop a marker opened right before the cut instead of leaving an empty entity
+    if opened {
+        match truncated.pop() {
+            Some('`') => in_code = false,
+            _ => in_bold = false,
+        }
+    }
+    if in_code {
+        truncated.push('`');
+    }
+    if in_bold {
+        truncated.push('*');
+    }
+    truncated.push('…');
+    truncated
+}
+/// Claim the updates of a bot for as long as the returned file is open
+///
+/// The claim is a lock on a file in `dir`, named after the bot's id, so it
+/// covers every process of the user and is released when the holder exits.
+fn lock_updates(dir: &Path, bot_token: &str) -> Result<File> {
+    let bot_id = bot_token.split(':').next().unwrap_or_default();
+    let path = dir.join(format!("telegram-{bot_id}.lock"));
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(file),
+        Err(TryLockError::WouldBlock) => {
+            anyhow::bail!("Another hook is already waiting for a reply from Telegram bot {bot_id}")
+        }
+        Err(TryLockError::Error(e)) => Err(e).with_context(|| format!("Failed to lock {}", path.display())),
+    }
+}
+
+/// Cut an action body to the callback data limit, on a character boundary
+fn callback_data(body: &str) -> &str {
+    let mut end = body.len().min(CALLBACK_DATA_MAX);
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    &body[..end]
+}
+
+/// Check that every button of a message comes back as sent when pressed
+///
+/// A reply cut to the callback data limit could not be matched, so waiting
+/// for it would always time out.
+fn ensure_callbacks_fit(message: &NtfyMessage) -> Result<()> {
+    for action in message.actions.iter().flatten().filter(|action| action.action == "http") {
+        if let Some(body) = action.body.as_ref().filter(|body| body.len() > CALLBACK_DATA_MAX) {
+            anyhow::bail!(
+                "The '{}' button's reply is {} bytes, but Telegram allows at most {}",
+                action.label,
+                body.len(),
+                CALLBACK_DATA_MAX
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntfy::NtfyAction;
+
+    fn client() -> TelegramClient {
+        let config = TelegramBackendConfig {
+            bot_token: "123:abc".to_string(),
+            chat_id: "42".to_string(),
+            api_url: "http://127.0.0.1:9".to_string(),
+            timeout_secs: Some(5),
+        };
+        TelegramClient::new(config, RetryConfig::default()).unwrap()
+    }
+
+    fn action(action: &str, label: &str, url: Option<&str>, body: Option<&str>) -> NtfyAction {
+        NtfyAction {
+            action: action.to_string(),
+            label: label.to_string(),
+            url: url.map(str::to_string),
+            method: None,
+            headers: None,
+            body: body.map(str::to_string),
+            clear: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_v2_conversion() {
+        assert_eq!(
+            to_markdown_v2("✅ **Edit** completed\n📁 `src/main.rs`"),
+            "✅ *Edit* completed\n📁 `src/main.rs`"
+        );
+        assert_eq!(to_markdown_v2("Done. (1-2) &quot;ok&quot;!"), "Done\\. \\(1\\-2\\) \"ok\"\\!");
+        assert_eq!(to_markdown_v2("**unbalanced"), "\\*\\*unbalanced");
+        assert_eq!(to_markdown_v2("`a\\b`"), "`a\\\\b`");
+    }
+
+    #[test]
+    fn test_long_messages_are_cut_after_escaping() {
+        let message = NtfyMessage {
+            title: Some("Build log".to_string()),
+            message: format!("**{}**", "a.".repeat(3000)),
+            ..Default::default()
+        };
+        let payload = client().build_payload(&message);
+        let text = payload["text"].as_str().unwrap();
+        assert!(text.chars().count() <= MESSAGE_MAX);
+        // Bold is closed and the last escape sequence is whole
+        assert!(text.ends_with("a*…") || text.ends_with("a\\.*…"), "{text}");
+
+        assert_eq!(truncate_markdown_v2("\\.\\.\\.", 4), "\\.…");
+        assert_eq!(truncate_markdown_v2("ab `c\\\\d`", 7), "ab `c`…");
+        assert_eq!(truncate_markdown_v2("abcd *efg*", 6), "abcd …");
+    }
+
+    #[test]
+    fn test_payload_with_inline_keyboard() {
+        let message = NtfyMessage {
+            title: Some("Approval needed: Bash".to_string()),
+            message: "💻 `rm -rf build`".to_string(),
+            priority: Some(5),
+            actions: Some(vec![
+                action("http", "Allow", Some("https://ntfy.sh/replies"), Some("allow 0123456789abcdef")),
+                action("view", "Docs", Some("https://example.com/docs"), None),
+                action("broadcast", "Ignored", None, None),
+            ]),
+            ..Default::default()
+        };
+
+        let payload = client().build_payload(&message);
+        assert_eq!(payload["chat_id"], "42");
+        assert_eq!(payload["parse_mode"], "MarkdownV2");
+        assert_eq!(payload["text"], "*Approval needed: Bash*\n\n💻 `rm -rf build`");
+        assert_eq!(payload["disable_notification"], false);
+
+        let row = &payload["reply_markup"]["inline_keyboard"][0];
+        assert_eq!(row.as_array().unwrap().len(), 2);
+        assert_eq!(row[0]["callback_data"], "allow 0123456789abcdef");
+        assert_eq!(row[1]["url"], "https://example.com/docs");
+    }
+
+    #[test]
+    fn test_one_waiter_per_bot() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let waiting = lock_updates(temp_dir.path(), "123:abc").unwrap();
+
+        let error = lock_updates(temp_dir.path(), "123:other").unwrap_err().to_string();
+        assert!(error.contains("already waiting"), "{error}");
+        assert!(lock_updates(temp_dir.path(), "456:abc").is_ok());
+
+        drop(waiting);
+        assert!(lock_updates(temp_dir.path(), "123:abc").is_ok());
+    }
+
+    #[test]
+    fn test_callback_data_is_cut_to_64_bytes() {
+        let long = format!("{}é", "a".repeat(63));
+        assert_eq!(callback_data(&long), "a".repeat(63));
+        assert_eq!(callback_data("deny 01"), "deny 01");
+
+        let mut message = NtfyMessage {
+            actions: Some(vec![action("http", "Allow", None, Some("allow 0123456789abcdef"))]),
+            ..Default::default()
+        };
+        assert!(ensure_callbacks_fit(&message).is_ok());
+
+        message.actions = Some(vec![action("http", "Allow", None, Some(&long))]);
+        let error = ensure_callbacks_fit(&message).unwrap_err().to_string();
+        assert!(error.contains("'Allow' button's reply is 65 bytes"), "{error}");
+    }
+
+    #[test]
+    fn test_reply_from_update() {
+        let client = client();
+
+        let press = json!({"update_id": 1, "callback_query": {
+            "id": "cb1", "data": "deny 01", "message": {"chat": {"id": 42}}
+        }});
+        assert_eq!(client.reply_from_update(&press), Some(("deny 01".to_string(), Some("cb1".to_string()))));
+
+        let text = json!({"update_id": 2, "message": {"text": "run the tests", "chat": {"id": 42}}});
+        assert_eq!(client.reply_from_update(&text), Some(("run the tests".to_string(), None)));
+
+        let other_chat = json!({"update_id": 3, "message": {"text": "hi", "chat": {"id": 7}}});
+        assert_eq!(client.reply_from_update(&other_chat), None);
+    }
+}
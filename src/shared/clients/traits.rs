@@ -1,7 +1,10 @@
 
 use async_trait::async_trait;
 use anyhow::Result;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use crate::ntfy::NtfyMessage;
 
@@ -10,8 +13,74 @@ use crate::ntfy::NtfyMessage;
 pub trait NotificationClient: Send + Sync {
     /// Send a notification message
     async fn send(&self, message: &NtfyMessage) -> Result<()>;
+
+    /// Send a notification rendered for a hook event
+    ///
+    /// Backends that format from the raw hook data override this; the
+    /// default ignores the event and sends the rendered message.
+    async fn send_event(&self, message: &NtfyMessage, _event: &HookEvent) -> Result<()> {
+        self.send(message).await
+    }
+}
+
+/// Hook that produced a notification
+#[derive(Debug, Clone, Default)]
+pub struct HookEvent {
+    /// The Claude Code hook name
+    pub hook_name: String,
+    /// Hook data after enhancement
+    pub data: serde_json::Value,
+}
+
+impl HookEvent {
+    pub fn new(hook_name: impl Into<String>, data: serde_json::Value) -> Self {
+        Self {
+            hook_name: hook_name.into(),
+            data,
+        }
+    }
+}
+
+/// Run a send operation with retries, recording the outcome in `stats`
+///
+/// `attempt` is called once per try; failures are retried with the delays
+/// from `retry_config` and the last error is returned once retries run out.
+pub async fn send_with_retry<F, Fut>(
+    retry_config: &RetryConfig,
+    stats: &Mutex<ClientStats>,
+    mut attempt: F,
+) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let start = Instant::now();
+    let mut result = attempt().await;
+
+    let mut tries = 0;
+    while result.is_err() && tries < retry_config.max_attempts {
+        if let Ok(mut stats) = stats.lock() {
+            stats.record_retry();
+        }
+        tokio::time::sleep(retry_config.calculate_delay(tries)).await;
+        tries += 1;
+        result = attempt().await;
+    }
+
+    let elapsed = start.elapsed().as_millis() as u64;
+    if let Ok(mut stats) = stats.lock() {
+        match &result {
+            Ok(_) => stats.record_success(elapsed),
+            Err(e) => stats.record_failure(e.to_string()),
+        }
+    }
+
+    result
 }
 
+/// Number of recent latencies kept for percentiles
+const LATENCY_WINDOW: usize = 1024;
+
 /// Performance and usage statistics for notification clients
 #[derive(Debug, Clone)]
 pub struct ClientStats {
@@ -21,6 +90,10 @@ pub struct ClientStats {
     pub messages_failed: u64,
     /// Average latency in milliseconds
     pub average_latency_ms: u64,
+    /// Sum of all recorded latencies in milliseconds
+    pub total_latency_ms: u64,
+    /// Latencies of the most recent successful sends, oldest first
+    pub recent_latencies_ms: VecDeque<u64>,
     /// Minimum recorded latency
     pub min_latency_ms: u64,
     /// Maximum recorded latency  
@@ -37,6 +110,8 @@ impl Default for ClientStats {
             messages_sent: 0,
             messages_failed: 0,
             average_latency_ms: 0,
+            total_latency_ms: 0,
+            recent_latencies_ms: VecDeque::new(),
             min_latency_ms: u64::MAX,
             max_latency_ms: 0,
             last_error: None,
@@ -49,11 +124,12 @@ impl ClientStats {
     /// Update statistics with a successful send operation
     pub fn record_success(&mut self, latency_ms: u64) {
         self.messages_sent += 1;
-        self.average_latency_ms = if self.messages_sent == 1 {
-            latency_ms
-        } else {
-            (self.average_latency_ms + latency_ms) / 2
-        };
+        self.total_latency_ms = self.total_latency_ms.saturating_add(latency_ms);
+        self.average_latency_ms = self.total_latency_ms / self.messages_sent;
+        if self.recent_latencies_ms.len() == LATENCY_WINDOW {
+            self.recent_latencies_ms.pop_front();
+        }
+        self.recent_latencies_ms.push_back(latency_ms);
         self.min_latency_ms = self.min_latency_ms.min(latency_ms);
         self.max_latency_ms = self.max_latency_ms.max(latency_ms);
     }
@@ -68,6 +144,16 @@ impl ClientStats {
     pub fn record_retry(&mut self) {
         self.retry_attempts += 1;
     }
+
+    /// Latency below which `percentile` percent of recent sends completed
+    ///
+    /// Uses the nearest-rank method; `None` until a send has succeeded.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<u64> {
+        let mut latencies: Vec<u64> = self.recent_latencies_ms.iter().copied().collect();
+        latencies.sort_unstable();
+        let rank = (percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+        latencies.get(rank.clamp(1, latencies.len().max(1)) - 1).copied()
+    }
     
 }
 
@@ -0,0 +1,197 @@
+//! Generic HTTP webhook backend
+//!
+//! POSTs a JSON document rendered from a user-defined Handlebars template to
+//! an arbitrary URL, for piping hook events into tools that have no native
+//! backend.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+use super::http::{build_client, ensure_success};
+use super::traits::{send_with_retry, ClientStats, HookEvent, NotificationClient, RetryConfig};
+use crate::ntfy::NtfyMessage;
+use crate::shared::config::WebhookBackendConfig;
+use crate::shared::templates::TemplateEngine;
+
+const BODY_TEMPLATE: &str = "body";
+
+/// Webhook client posting templated JSON bodies
+pub struct WebhookClient {
+    client: Client,
+    config: WebhookBackendConfig,
+    templates: TemplateEngine,
+    header_templates: TemplateEngine,
+    retry_config: RetryConfig,
+    stats: Arc<Mutex<ClientStats>>,
+}
+
+impl WebhookClient {
+    /// Create a webhook client, compiling its body and header templates
+    pub fn new(config: WebhookBackendConfig, retry_config: RetryConfig) -> Result<Self> {
+        let mut templates = TemplateEngine::new_for_json();
+        if let Some(body) = &config.body {
+            templates.register_template(BODY_TEMPLATE, body)?;
+        }
+        // Header values are plain text, not JSON string contents
+        let mut header_templates = TemplateEngine::new_for_text();
+        for (name, value) in &config.headers {
+            header_templates.register_template(name, value)?;
+        }
+
+        Ok(Self {
+            client: build_client(config.timeout_secs)?,
+            config,
+            templates,
+            header_templates,
+            retry_config,
+            stats: Arc::new(Mutex::new(ClientStats::default())),
+        })
+    }
+
+    /// Render the JSON body for a notification
+    fn render_body(&self, context: &Value) -> Result<Value> {
+        if self.config.body.is_none() {
+            return Ok(context.clone());
+        }
+
+        let rendered = self.templates.render(BODY_TEMPLATE, context)?;
+        serde_json::from_str(&rendered)
+            .with_context(|| format!("Webhook body template did not render valid JSON: {rendered}"))
+    }
+
+    /// Render the configured headers for a notification
+    fn render_headers(&self, context: &Value) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for name in self.config.headers.keys() {
+            let value = self.header_templates.render(name, context)?;
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).with_context(|| format!("Invalid header name: {name}"))?,
+                HeaderValue::from_str(&value).with_context(|| format!("Invalid value for header {name}"))?,
+            );
+        }
+        Ok(headers)
+    }
+
+    async fn send_internal(&self, body: &Value, headers: &HeaderMap) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.config.url)
+            .headers(headers.clone())
+            .json(body)
+            .send()
+            .await
+            .context("Failed to send webhook request")?;
+
+        ensure_success(response, "Webhook").await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationClient for WebhookClient {
+    async fn send(&self, message: &NtfyMessage) -> Result<()> {
+        self.send_event(message, &HookEvent::default()).await
+    }
+
+    async fn send_event(&self, message: &NtfyMessage, event: &HookEvent) -> Result<()> {
+        let context = template_context(message, event);
+        let body = self.render_body(&context)?;
+        let headers = self.render_headers(&context)?;
+
+        send_with_retry(&self.retry_config, &self.stats, || self.send_internal(&body, &headers)).await
+    }
+}
+
+/// Template context shared by the body and header templates
+pub(super) fn template_context(message: &NtfyMessage, event: &HookEvent) -> Value {
+    json!({
+        "title": message.title,
+        "message": message.message,
+        "priority": message.priority,
+        "tags": message.tags.clone().unwrap_or_default(),
+        "topic": message.topic,
+        "click": message.click,
+        "hook_name": event.hook_name,
+        "hook": event.data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn webhook(body: Option<&str>) -> WebhookClient {
+        let config = WebhookBackendConfig {
+            url: "http://127.0.0.1:9/hook".to_string(),
+            headers: HashMap::from([
+                ("X-Source".to_string(), "claude-{{hook_name}}".to_string()),
+                ("X-Title".to_string(), "{{title}}".to_string()),
+            ]),
+            body: body.map(str::to_string),
+            timeout_secs: Some(5),
+        };
+        WebhookClient::new(config, RetryConfig::default()).unwrap()
+    }
+
+    fn sample() -> (NtfyMessage, HookEvent) {
+        let message = NtfyMessage {
+            title: Some("Tool \"Bash\" failed".to_string()),
+            message: "exit code 1\nsee logs".to_string(),
+            priority: Some(4),
+            ..Default::default()
+        };
+        let event = HookEvent::new("PostToolUse", json!({"tool_name": "Bash", "cwd": "/src"}));
+        (message, event)
+    }
+
+    #[test]
+    fn test_body_template_escapes_json() {
+        let client = webhook(Some(
+            r#"{"summary": "{{title}}", "details": "{{message}}", "severity": {{priority}}, "payload": {{json hook}}}"#,
+        ));
+        let (message, event) = sample();
+
+        let body = client.render_body(&template_context(&message, &event)).unwrap();
+        assert_eq!(body["summary"], "Tool \"Bash\" failed");
+        assert_eq!(body["details"], "exit code 1\nsee logs");
+        assert_eq!(body["severity"], 4);
+        assert_eq!(body["payload"]["cwd"], "/src");
+    }
+
+    #[test]
+    fn test_default_body_and_headers() {
+        let client = webhook(None);
+        let (message, event) = sample();
+        let context = template_context(&message, &event);
+
+        let body = client.render_body(&context).unwrap();
+        assert_eq!(body["hook_name"], "PostToolUse");
+        assert_eq!(body["hook"]["tool_name"], "Bash");
+
+        let headers = client.render_headers(&context).unwrap();
+        assert_eq!(headers["x-source"], "claude-PostToolUse");
+    }
+
+    #[test]
+    fn test_header_values_are_not_json_escaped() {
+        let client = webhook(None);
+        let (mut message, event) = sample();
+        message.title = Some(r#"Tool "Bash" failed in C:\src"#.to_string());
+
+        let headers = client.render_headers(&template_context(&message, &event)).unwrap();
+        assert_eq!(headers["x-title"], r#"Tool "Bash" failed in C:\src"#);
+    }
+
+    #[test]
+    fn test_invalid_json_body_is_rejected() {
+        let client = webhook(Some(r#"{"summary": {{title}}}"#));
+        let (message, event) = sample();
+
+        assert!(client.render_body(&template_context(&message, &event)).is_err());
+    }
+}
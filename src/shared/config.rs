@@ -1,3 +1,6 @@
+use crate::hooks::decision::PermissionDecision;
+use crate::shared::clients::traits::RetryConfig;
+use crate::shared::templates::TemplateStyle;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -12,6 +15,14 @@ pub struct Config {
     pub hooks: HookConfig,
     pub templates: TemplateConfig,
     pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+    #[serde(default)]
+    pub reply: ReplyConfig,
+    #[serde(default)]
+    pub retry: SendRetryConfig,
+    #[serde(default)]
+    pub backends: Vec<BackendConfig>,
 }
 
 /// Configuration for ntfy notification service integration
@@ -37,6 +48,8 @@ pub struct HookConfig {
     pub never_filter_decision_hooks: bool, // Always allow decision-requiring hooks
     #[serde(default = "default_decision_hook_priority")]
     pub decision_hook_priority: u8, // Priority for hooks that require user decisions
+    #[serde(default)]
+    pub routes: HashMap<String, Vec<String>>, // hook_name (or "*") -> backend names
 }
 
 fn default_never_filter_decision_hooks() -> bool {
@@ -52,17 +65,368 @@ pub struct TemplateConfig {
     pub use_custom: bool,
     pub custom_templates: HashMap<String, String>, // hook_name -> template
     pub variables: HashMap<String, String>,        // custom variables
+    #[serde(default)]
+    pub style: TemplateStyle, // built-in tags: "rich" or "compact"
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DaemonConfig {
     pub enabled: bool,
+    #[serde(default)]
+    pub scope: DaemonScope, // one daemon for every project, or one per project
     pub socket_path: Option<PathBuf>,
     pub log_level: String,
     pub log_path: Option<String>,
     pub max_queue_size: usize,
     pub retry_attempts: u32,
     pub retry_delay_secs: u64,
+    #[serde(default)]
+    pub autostart: bool, // start the daemon from a hook when it is not running
+    #[serde(default = "default_queue_aging_secs")]
+    pub queue_aging_secs: u64, // waiting this long raises a queued task by one priority level
+    #[serde(default)]
+    pub drop_policy: DropPolicy, // which task to shed once max_queue_size is reached
+    #[serde(default = "default_workers")]
+    pub workers: usize, // tasks delivered at the same time
+    #[serde(default = "default_max_per_server")]
+    pub max_per_server: usize, // concurrent deliveries to one host
+    #[serde(default)]
+    pub watch_config: bool, // reload when the daemon or a project config file changes
+    pub listen_address: Option<String>, // also accept clients over TCP, e.g. "0.0.0.0:7878"
+    pub auth_token: Option<String>, // shared secret of TCP clients and the daemon
+    pub tls_cert: Option<PathBuf>, // PEM certificate chain for TLS on listen_address
+    pub tls_key: Option<PathBuf>, // PEM private key for TLS on listen_address
+    pub remote_address: Option<String>, // send hooks to a daemon elsewhere, e.g. "tls://laptop:7878"
+    pub remote_ca_cert: Option<PathBuf>, // PEM CA certificate trusted for remote_address
+}
+
+fn default_queue_aging_secs() -> u64 {
+    30
+}
+
+fn default_workers() -> usize {
+    4
+}
+
+fn default_max_per_server() -> usize {
+    2
+}
+
+/// Which daemon hooks and `claude-ntfy daemon` commands talk to
+///
+/// Outside of a project the global daemon is used either way.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonScope {
+    #[default]
+    Global,  // one daemon in ~/.claude/ntfy-service
+    Project, // a daemon in the project's .claude/ntfy-service
+}
+
+/// Which queued task the daemon sheds when its queue is full
+///
+/// Decision hooks are never shed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DropPolicy {
+    #[default]
+    LowestPriority, // the task that would be delivered last
+    Oldest,         // the task that has waited longest
+    Newest,         // the incoming task
+}
+
+/// Named notification backend from a `[[backends]]` entry
+///
+/// Hooks are routed to backends by name through `[hooks.routes]`. The
+/// `[ntfy]` section is always available as a backend named `ntfy`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackendConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: BackendKind,
+}
+
+/// Backend type and its type-specific settings, selected by `type = "..."`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BackendKind {
+    Ntfy(NtfyBackendConfig),
+    Webhook(WebhookBackendConfig),
+    Slack(ChatBackendConfig),
+    Discord(ChatBackendConfig),
+    Gotify(GotifyBackendConfig),
+    Pushover(PushoverBackendConfig),
+    Telegram(TelegramBackendConfig),
+    Smtp(SmtpBackendConfig),
+    Command(CommandBackendConfig),
+}
+
+impl BackendKind {
+    /// The `type` name used in configuration
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            BackendKind::Ntfy(_) => "ntfy",
+            BackendKind::Webhook(_) => "webhook",
+            BackendKind::Slack(_) => "slack",
+            BackendKind::Discord(_) => "discord",
+            BackendKind::Gotify(_) => "gotify",
+            BackendKind::Pushover(_) => "pushover",
+            BackendKind::Telegram(_) => "telegram",
+            BackendKind::Smtp(_) => "smtp",
+            BackendKind::Command(_) => "command",
+        }
+    }
+
+    /// Host this backend delivers to, shared by every backend on that host
+    ///
+    /// Local commands have no server; they are limited by `max_concurrent`.
+    pub fn server(&self) -> Option<String> {
+        let url = match self {
+            BackendKind::Ntfy(ntfy) => &ntfy.server_url,
+            BackendKind::Webhook(webhook) => &webhook.url,
+            BackendKind::Slack(chat) | BackendKind::Discord(chat) => &chat.webhook_url,
+            BackendKind::Gotify(gotify) => &gotify.server_url,
+            BackendKind::Pushover(pushover) => &pushover.api_url,
+            BackendKind::Telegram(telegram) => &telegram.api_url,
+            BackendKind::Smtp(smtp) => return Some(smtp.host.to_lowercase()),
+            BackendKind::Command(_) => return None,
+        };
+        let without_scheme = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+        let host = without_scheme.split(['/', '?', '#']).next().unwrap_or_default();
+        Some(host.to_lowercase())
+    }
+}
+
+/// Settings for an additional ntfy server or topic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NtfyBackendConfig {
+    pub server_url: String,
+    pub topic: Option<String>, // overrides the per-hook topic
+    pub auth_token: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// Settings for a generic HTTP webhook
+///
+/// `body` and header values are Handlebars templates rendered with the
+/// notification (`title`, `message`, `priority`, `tags`, `topic`, `click`),
+/// `hook_name`, and the raw hook data as `hook`. Without a body template the
+/// whole context is posted as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookBackendConfig {
+    pub url: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>, // must render to valid JSON
+    pub timeout_secs: Option<u64>,
+}
+
+/// Settings for a Slack or Discord incoming webhook
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatBackendConfig {
+    pub webhook_url: String,
+    pub username: Option<String>, // overrides the webhook's default name
+    pub timeout_secs: Option<u64>,
+}
+
+/// Settings for a Gotify server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GotifyBackendConfig {
+    pub server_url: String,
+    pub app_token: String,
+    pub timeout_secs: Option<u64>,
+}
+
+/// Settings for the Pushover API
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PushoverBackendConfig {
+    pub app_token: String,
+    pub user_key: String,
+    pub device: Option<String>,
+    #[serde(default = "default_pushover_api_url")]
+    pub api_url: String,
+    #[serde(default = "default_emergency_retry_secs")]
+    pub emergency_retry_secs: u32, // how often priority 5 is re-alerted (min 30)
+    #[serde(default = "default_emergency_expire_secs")]
+    pub emergency_expire_secs: u32, // when re-alerting stops (max 10800)
+    pub timeout_secs: Option<u64>,
+}
+
+fn default_pushover_api_url() -> String {
+    "https://api.pushover.net".to_string()
+}
+
+fn default_emergency_retry_secs() -> u32 {
+    60
+}
+
+fn default_emergency_expire_secs() -> u32 {
+    3600
+}
+
+/// Settings for a Telegram bot
+///
+/// Replies are read with `getUpdates` long polling, which Telegram refuses
+/// while the bot has a webhook set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TelegramBackendConfig {
+    pub bot_token: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub chat_id: String,
+    #[serde(default = "default_telegram_api_url")]
+    pub api_url: String,
+    pub timeout_secs: Option<u64>,
+}
+
+fn default_telegram_api_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+/// Settings for sending email through an SMTP server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SmtpBackendConfig {
+    pub host: String,
+    pub port: Option<u16>, // defaults to 587, 465 or 25 depending on `security`
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    #[default]
+    Starttls, // upgrade a plain connection, required
+    Tls,      // implicit TLS from the first byte
+    Plain,    // unencrypted, for local relays only
+}
+
+impl SmtpSecurity {
+    /// Conventional port for this security mode
+    pub fn default_port(self) -> u16 {
+        match self {
+            SmtpSecurity::Starttls => 587,
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::Plain => 25,
+        }
+    }
+}
+
+/// Settings for running a local command per notification
+///
+/// `args` are Handlebars templates rendered without escaping, with the same
+/// values as webhook templates. The raw hook data is written to stdin as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandBackendConfig {
+    pub command: String, // program name or path, no shell involved
+    #[serde(default = "default_command_args")]
+    pub args: Vec<String>,
+    pub timeout_secs: Option<u64>, // the command is killed after this (default 10)
+    #[serde(default = "default_command_max_concurrent")]
+    pub max_concurrent: usize, // runs of this command at once; others wait
+}
+
+fn default_command_args() -> Vec<String> {
+    vec!["{{title}}".to_string(), "{{message}}".to_string(), "{{priority}}".to_string()]
+}
+
+fn default_command_max_concurrent() -> usize {
+    1
+}
+
+/// Accept ids written either as TOML strings or integers
+fn string_or_number<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        String(String),
+        Number(i64),
+    }
+
+    Ok(match Id::deserialize(deserializer)? {
+        Id::String(id) => id,
+        Id::Number(id) => id.to_string(),
+    })
+}
+
+/// Remote approval settings for PreToolUse hooks
+///
+/// When enabled, matching tool calls block until the user answers an
+/// Allow/Deny notification, or the timeout elapses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    pub enabled: bool,
+    pub tools: Vec<String>, // tool name patterns requiring approval (empty = all tools)
+    pub reply_topic: Option<String>, // defaults to "<default_topic>-replies"
+    pub timeout_secs: u64,
+    pub timeout_decision: PermissionDecision, // decision when nobody answers in time
+    pub backend: Option<String>, // telegram backend to ask instead of ntfy
+    pub reply_token: Option<String>, // publish-only token for the reply topic, sent by the buttons
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        ApprovalConfig {
+            enabled: false,
+            tools: vec!["Bash".to_string()],
+            reply_topic: None,
+            timeout_secs: 120,
+            timeout_decision: PermissionDecision::Ask,
+            backend: None,
+            reply_token: None,
+        }
+    }
+}
+
+/// Two-way Stop notifications
+///
+/// When enabled, the Stop notification carries a Reply action pointing at a
+/// per-session topic; a reply received within `wait_secs` keeps Claude going.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplyConfig {
+    pub enabled: bool,
+    pub wait_secs: u64, // 0 = send the Reply action without waiting
+    pub topic_prefix: Option<String>, // defaults to "<default_topic>-reply"
+    pub backend: Option<String>, // telegram backend to reply through instead of ntfy
+}
+
+impl Default for ReplyConfig {
+    fn default() -> Self {
+        ReplyConfig {
+            enabled: false,
+            wait_secs: 50,
+            topic_prefix: None,
+            backend: None,
+        }
+    }
+}
+
+/// Retries of each send to a backend
+///
+/// These happen within one delivery attempt; `daemon.retry_attempts` then
+/// retries the backends that still failed, much later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SendRetryConfig {
+    pub attempts: u32, // retries after the first attempt
+    pub base_delay_ms: u64, // delay before the first retry, doubled for each further one
+}
+
+impl Default for SendRetryConfig {
+    fn default() -> Self {
+        let retry = RetryConfig::default();
+        SendRetryConfig {
+            attempts: retry.max_attempts,
+            base_delay_ms: retry.base_delay_ms,
+        }
+    }
 }
 
 impl Config {
@@ -113,21 +477,40 @@ impl Default for Config {
                 filters: HashMap::new(),
                 never_filter_decision_hooks: true,
                 decision_hook_priority: 5, // Max priority for decision hooks
+                routes: HashMap::new(),
             },
             templates: TemplateConfig {
                 use_custom: false,
                 custom_templates: HashMap::new(),
                 variables: HashMap::new(),
+                style: TemplateStyle::default(),
             },
             daemon: DaemonConfig {
                 enabled: true,
+                scope: DaemonScope::default(),
                 socket_path: None,
                 log_level: "info".to_string(),
                 log_path: None, // Default to None, will use console logging
                 max_queue_size: 1000,
                 retry_attempts: 3,
                 retry_delay_secs: 5,
+                autostart: false,
+                queue_aging_secs: default_queue_aging_secs(),
+                drop_policy: DropPolicy::default(),
+                workers: default_workers(),
+                max_per_server: default_max_per_server(),
+                watch_config: false,
+                listen_address: None,
+                auth_token: None,
+                tls_cert: None,
+                tls_key: None,
+                remote_address: None,
+                remote_ca_cert: None,
             },
+            approval: ApprovalConfig::default(),
+            reply: ReplyConfig::default(),
+            retry: SendRetryConfig::default(),
+            backends: Vec::new(),
         }
     }
 }
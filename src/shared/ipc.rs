@@ -3,10 +3,12 @@
 //! This module provides a unified interface for communicating with the daemon
 //! via Unix domain sockets, reducing code duplication across handlers.
 
+use crate::daemon::shared::{DaemonEvent, EventFilter};
+use crate::daemon::protocol::{Connection, Encoding};
+use crate::daemon::remote::{RemoteDaemon, Stream};
 use crate::daemon::{DaemonMessage, DaemonResponse};
 use anyhow::{Context, Result};
 use std::path::Path;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::UnixStream;
 use tracing::debug;
 
@@ -15,12 +17,15 @@ use tracing::debug;
 pub struct IpcClientConfig {
     /// Maximum allowed response size in bytes
     pub max_response_size: usize,
+    /// Encoding of messages and responses
+    pub encoding: Encoding,
 }
 
 impl Default for IpcClientConfig {
     fn default() -> Self {
         Self {
             max_response_size: 1024 * 1024, // 1MB default
+            encoding: Encoding::default(),
         }
     }
 }
@@ -30,6 +35,7 @@ impl IpcClientConfig {
     pub fn small_response() -> Self {
         Self {
             max_response_size: 1024, // 1KB
+            ..Self::default()
         }
     }
     
@@ -37,6 +43,7 @@ impl IpcClientConfig {
     pub fn large_response() -> Self {
         Self {
             max_response_size: 1024 * 1024, // 1MB
+            ..Self::default()
         }
     }
 }
@@ -64,60 +71,70 @@ impl IpcClient {
     where
         T: serde::de::DeserializeOwned,
     {
-        debug!("Sending IPC message to daemon at {}", socket_path.display());
-        
-        // Connect to Unix socket
-        let mut stream = UnixStream::connect(socket_path)
-            .await
-            .context("Failed to connect to daemon socket")?;
-
-        // Serialize message
-        let serialized = bincode::serde::encode_to_vec(&message, bincode::config::standard())
-            .context("Failed to serialize message")?;
-
-        let length = serialized.len() as u32;
-        let length_bytes = length.to_le_bytes();
+        let stream = Self::connect_local(socket_path).await?;
+        self.exchange(stream, None, &message).await
+    }
 
-        // Send length prefix
-        stream.write_all(&length_bytes).await
-            .context("Failed to write message length")?;
+    /// Send a message to a daemon on another host and expect a typed response
+    pub async fn send_remote_message<T>(&self, remote: &RemoteDaemon, message: DaemonMessage) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        debug!("Sending message to remote daemon at {}", remote);
+        let stream = remote.connect().await?;
+        self.exchange(stream, Some(remote.token()), &message).await
+    }
 
-        // Send message payload
-        stream.write_all(&serialized).await
-            .context("Failed to write message payload")?;
+    /// Subscribe to live task events matching `filter`
+    pub async fn subscribe(&self, socket_path: &Path, filter: EventFilter) -> Result<EventStream> {
+        let stream = Self::connect_local(socket_path).await?;
+        let mut connection = self.request(stream, None, &DaemonMessage::Subscribe(filter)).await?;
+        match connection.recv().await? {
+            Some(DaemonResponse::Ok) => Ok(EventStream { connection }),
+            Some(DaemonResponse::Error(e)) => Err(anyhow::anyhow!("Failed to subscribe to events: {}", e)),
+            _ => Err(anyhow::anyhow!("Unexpected response from daemon")),
+        }
+    }
 
-        stream.flush().await
-            .context("Failed to flush message")?;
+    async fn connect_local(socket_path: &Path) -> Result<UnixStream> {
+        debug!("Sending IPC message to daemon at {}", socket_path.display());
+        UnixStream::connect(socket_path)
+            .await
+            .context("Failed to connect to daemon socket")
+    }
 
+    /// Send one message and wait for the response
+    async fn exchange<S: Stream, T>(&self, stream: S, token: Option<&str>, message: &DaemonMessage) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut connection = self.request(stream, token, message).await?;
         debug!("Message sent, waiting for response");
 
-        // Read response length
-        let mut length_bytes = [0u8; 4];
-        stream.read_exact(&mut length_bytes).await
-            .context("Failed to read response length")?;
+        let response = connection
+            .recv()
+            .await?
+            .context("Daemon closed the connection without responding")?;
+        debug!("Received and deserialized response successfully");
+        Ok(response)
+    }
 
-        let response_length = u32::from_le_bytes(length_bytes) as usize;
+    /// Complete the handshake and send one message
+    async fn request<S: Stream>(&self, stream: S, token: Option<&str>, message: &DaemonMessage) -> Result<Connection<S>> {
+        let mut connection =
+            Connection::connect(stream, self.config.encoding, self.config.max_response_size, token).await?;
 
-        // Validate response length
-        if response_length > self.config.max_response_size {
+        // Fail clearly rather than send a message an older daemon cannot decode
+        let capability = message.capability();
+        if !connection.supports(capability) {
             return Err(anyhow::anyhow!(
-                "Response too large: {} bytes (max: {})",
-                response_length,
-                self.config.max_response_size
+                "The running daemon does not support '{}'; restart it with 'claude-ntfy daemon stop' and 'claude-ntfy daemon start'",
+                capability
             ));
         }
 
-        // Read response payload
-        let mut response_buffer = vec![0u8; response_length];
-        stream.read_exact(&mut response_buffer).await
-            .context("Failed to read response payload")?;
-
-        // Deserialize response
-        let (response, _): (T, usize) = bincode::serde::decode_from_slice(&response_buffer, bincode::config::standard())
-            .context("Failed to deserialize response")?;
-
-        debug!("Received and deserialized response successfully");
-        Ok(response)
+        connection.send(message).await?;
+        Ok(connection)
     }
     
     /// Send a message to daemon and expect a DaemonResponse
@@ -132,6 +149,22 @@ impl IpcClient {
     }
 }
 
+/// Events streamed by the daemon after [`IpcClient::subscribe`]
+pub struct EventStream {
+    connection: Connection<UnixStream>,
+}
+
+impl EventStream {
+    /// Wait for the next event; `None` once the daemon closes the stream
+    pub async fn next(&mut self) -> Result<Option<DaemonEvent>> {
+        match self.connection.recv().await? {
+            Some(DaemonResponse::Event(event)) => Ok(Some(*event)),
+            Some(_) => Err(anyhow::anyhow!("Unexpected response from daemon")),
+            None => Ok(None),
+        }
+    }
+}
+
 impl Default for IpcClient {
     fn default() -> Self {
         Self::new()
@@ -150,6 +183,16 @@ pub mod convenience {
         client.send_fire_and_forget(socket_path, message).await
     }
     
+    /// Send a notification task to a daemon on another host
+    pub async fn send_remote_notification_task(remote: &RemoteDaemon, task: NotificationTask) -> Result<()> {
+        let client = IpcClient::with_config(IpcClientConfig::small_response());
+        let message = DaemonMessage::Submit(Box::new(task));
+        match client.send_remote_message(remote, message).await? {
+            DaemonResponse::Error(e) => Err(anyhow::anyhow!("Remote daemon rejected the task: {}", e)),
+            _ => Ok(()),
+        }
+    }
+
     /// Get daemon status
     pub async fn get_daemon_status(socket_path: &Path) -> Result<DaemonResponse> {
         let client = IpcClient::with_config(IpcClientConfig::large_response());
@@ -167,6 +210,30 @@ pub mod convenience {
         let client = IpcClient::with_config(IpcClientConfig::small_response());
         client.send_daemon_message(socket_path, DaemonMessage::Reload).await
     }
+
+    /// List the daemon's dead letters
+    pub async fn list_dead_letters(socket_path: &Path) -> Result<DaemonResponse> {
+        let client = IpcClient::with_config(IpcClientConfig::large_response());
+        client.send_daemon_message(socket_path, DaemonMessage::DeadLetters).await
+    }
+
+    /// Retry one dead letter, or all of them when `id` is `None`
+    pub async fn retry_dead_letters(socket_path: &Path, id: Option<String>) -> Result<DaemonResponse> {
+        let client = IpcClient::with_config(IpcClientConfig::small_response());
+        client.send_daemon_message(socket_path, DaemonMessage::RetryDeadLetters(id)).await
+    }
+
+    /// Purge one dead letter, or all of them when `id` is `None`
+    pub async fn purge_dead_letters(socket_path: &Path, id: Option<String>) -> Result<DaemonResponse> {
+        let client = IpcClient::with_config(IpcClientConfig::small_response());
+        client.send_daemon_message(socket_path, DaemonMessage::PurgeDeadLetters(id)).await
+    }
+
+    /// Stream live task events matching `filter`
+    pub async fn subscribe_events(socket_path: &Path, filter: EventFilter) -> Result<EventStream> {
+        let client = IpcClient::with_config(IpcClientConfig::large_response());
+        client.subscribe(socket_path, filter).await
+    }
 }
 
 #[cfg(test)]
@@ -6,20 +6,16 @@ use serde_json::Value;
 use std::collections::HashMap;
 
 /// Template style configuration
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TemplateStyle {
-    /// Rich formatting with emojis and detailed information (for CLI)
+    /// Rich formatting with emojis and detailed information
+    #[default]
     Rich,
-    /// Compact formatting with minimal emojis (for daemon)
+    /// Compact formatting with a single built-in tag per hook
     Compact,
 }
 
-impl Default for TemplateStyle {
-    fn default() -> Self {
-        TemplateStyle::Rich
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct TemplateEngine {
     handlebars: Handlebars<'static>,
@@ -47,6 +43,47 @@ impl TemplateEngine {
         })
     }
 
+    /// Create an engine for user templates that render JSON documents
+    ///
+    /// Values are escaped as JSON string contents instead of HTML, so
+    /// `"{{title}}"` is always a valid string, and the `json` helper embeds
+    /// any value as a JSON literal (e.g. `{{json hook}}`). No default
+    /// templates are registered.
+    pub fn new_for_json() -> Self {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(false);
+        handlebars.register_escape_fn(escape_json_string);
+        handlebars.register_helper("json", Box::new(json_helper));
+
+        TemplateEngine {
+            handlebars,
+            default_templates: HashMap::new(),
+        }
+    }
+
+    /// Create an engine for user templates that render plain text
+    ///
+    /// Values are written unescaped, e.g. for command-line arguments. The
+    /// `json` helper is available as in [`TemplateEngine::new_for_json`].
+    pub fn new_for_text() -> Self {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(false);
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.register_helper("json", Box::new(json_helper));
+
+        TemplateEngine {
+            handlebars,
+            default_templates: HashMap::new(),
+        }
+    }
+
+    /// Register a user-supplied template under `name`
+    pub fn register_template(&mut self, name: &str, template: &str) -> Result<()> {
+        self.handlebars
+            .register_template_string(name, template)
+            .context(format!("Failed to parse template: {name}"))
+    }
+
     fn create_default_templates() -> HashMap<String, String> {
         let mut templates = HashMap::new();
         Self::create_rich_templates(&mut templates);
@@ -152,6 +189,18 @@ impl TemplateEngine {
     }
 
 
+    /// Render an ad-hoc template string with the same settings as named templates
+    pub fn render_string(&self, template: &str, data: &Value) -> Result<String> {
+        self.handlebars
+            .render_template(template, data)
+            .context("Failed to render template string")
+    }
+
+    /// Whether a template is registered under `name`
+    pub fn has_template(&self, name: &str) -> bool {
+        self.handlebars.has_template(name)
+    }
+
     // Format hook data for compatibility with old API
     pub fn format_hook_data(&self, _hook_name: &str, hook_data: &Value) -> Value {
         // Add timestamp to hook data
@@ -173,6 +222,92 @@ impl TemplateEngine {
 
 }
 
+/// Template used for hooks without a built-in template
+const GENERIC_TEMPLATE: &str = "generic";
+
+/// Renders notification titles and bodies for hooks
+///
+/// The CLI and the daemon both render through this type, so a hook looks the
+/// same whether or not it was delivered through the daemon.
+#[derive(Debug, Clone)]
+pub struct NotificationRenderer {
+    template_engine: TemplateEngine,
+    formatter: MessageFormatter,
+    compact: MessageFormatter,
+}
+
+impl NotificationRenderer {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            template_engine: TemplateEngine::new_with_style(TemplateStyle::Rich)?,
+            formatter: MessageFormatter::new(TemplateStyle::Rich),
+            compact: MessageFormatter::new(TemplateStyle::Compact),
+        })
+    }
+
+    /// Render the title and body for a hook
+    ///
+    /// `variables` are added to the top level of the template context without
+    /// shadowing hook data. A custom template that fails to render falls back
+    /// to the built-in template for the hook.
+    pub fn render(
+        &self,
+        hook_name: &str,
+        hook_data: &Value,
+        custom_template: Option<&str>,
+        variables: &HashMap<String, String>,
+    ) -> Result<(String, String)> {
+        let mut data = self.template_engine.format_hook_data(hook_name, hook_data);
+        if let Value::Object(map) = &mut data {
+            for (name, value) in variables {
+                map.entry(name.clone()).or_insert_with(|| Value::String(value.clone()));
+            }
+            map.entry("hook_name").or_insert_with(|| Value::String(hook_name.to_string()));
+        }
+
+        let custom_body = custom_template.and_then(|template| {
+            self.template_engine
+                .render_string(template, &data)
+                .inspect_err(|e| tracing::error!("Failed to render custom template for {}: {:#}", hook_name, e))
+                .ok()
+        });
+        let body = match custom_body {
+            Some(body) => body,
+            None if self.template_engine.has_template(hook_name) => self.template_engine.render(hook_name, &data)?,
+            None => self.template_engine.render(GENERIC_TEMPLATE, &data)?,
+        };
+
+        Ok((self.formatter.format_title(hook_name, &data), body))
+    }
+
+    /// Built-in tags for a hook in the given style, if it has any
+    pub fn tags(&self, hook_name: &str, style: TemplateStyle) -> Vec<String> {
+        match style {
+            TemplateStyle::Rich => self.formatter.get_tags(hook_name),
+            TemplateStyle::Compact => self.compact.get_tags(hook_name),
+        }
+    }
+}
+
+/// Escape a rendered value so it can be placed inside a JSON string
+fn escape_json_string(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// `{{json value}}` - write a value as a JSON literal
+fn json_helper(
+    helper: &handlebars::Helper,
+    _: &Handlebars,
+    _: &handlebars::Context,
+    _: &mut handlebars::RenderContext,
+    out: &mut dyn handlebars::Output,
+) -> handlebars::HelperResult {
+    let value = helper.param(0).map(|p| p.value().clone()).unwrap_or(Value::Null);
+    out.write(&value.to_string())?;
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageFormatter {
     pub title_template: Option<String>,
@@ -0,0 +1,330 @@
+//! Integration tests for routing hooks to named notification backends
+
+mod mock_http;
+mod mock_ntfy;
+mod mock_smtp;
+
+use assert_cmd::Command;
+use claude_ntfy::shared::config::{
+    BackendConfig, BackendKind, ChatBackendConfig, CommandBackendConfig, GotifyBackendConfig, NtfyBackendConfig,
+    PushoverBackendConfig, SmtpBackendConfig, SmtpSecurity, WebhookBackendConfig,
+};
+use claude_ntfy::Config;
+use mock_http::MockHttpServer;
+use mock_ntfy::MockNtfyServer;
+use mock_smtp::MockSmtpServer;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+const POST_TOOL_USE: &str = r#"{"hook_event_name": "PostToolUse", "tool_name": "Write", "tool_input": {"file_path": "src/lib.rs"}}"#;
+
+/// Write a project config with extra backends, routing PostToolUse to `routes`
+fn project_with_backend(ntfy_url: &str, backends: Vec<BackendConfig>, routes: &[&str]) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.ntfy.server_url = ntfy_url.to_string();
+    config.daemon.enabled = false;
+    config.backends = backends;
+    config
+        .hooks
+        .routes
+        .insert("PostToolUse".to_string(), routes.iter().map(|r| r.to_string()).collect());
+
+    let config_dir = temp_dir.path().join(".claude/ntfy-service");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), toml::to_string_pretty(&config).unwrap()).unwrap();
+    temp_dir
+}
+
+/// Write a project config with an extra `desk` ntfy backend on a second server
+fn project_with_backends(phone_url: &str, desk_url: &str, routes: &[&str]) -> TempDir {
+    let desk = BackendConfig {
+        name: "desk".to_string(),
+        kind: BackendKind::Ntfy(NtfyBackendConfig {
+            server_url: desk_url.to_string(),
+            topic: Some("desk-alerts".to_string()),
+            auth_token: None,
+            timeout_secs: Some(5),
+        }),
+    };
+    project_with_backend(phone_url, vec![desk], routes)
+}
+
+fn webhook_backend(url: &str, body: Option<&str>) -> BackendConfig {
+    BackendConfig {
+        name: "incidents".to_string(),
+        kind: BackendKind::Webhook(WebhookBackendConfig {
+            url: format!("{url}/events"),
+            headers: HashMap::from([("Authorization".to_string(), "Token secret".to_string())]),
+            body: body.map(str::to_string),
+            timeout_secs: Some(5),
+        }),
+    }
+}
+
+fn chat_backend(name: &str, url: &str) -> BackendConfig {
+    let config = ChatBackendConfig {
+        webhook_url: format!("{url}/webhook"),
+        username: None,
+        timeout_secs: Some(5),
+    };
+    let kind = match name {
+        "slack" => BackendKind::Slack(config),
+        _ => BackendKind::Discord(config),
+    };
+    BackendConfig {
+        name: name.to_string(),
+        kind,
+    }
+}
+
+fn run_hook(project: &TempDir) -> std::process::Output {
+    Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .arg("--project")
+        .arg(project.path())
+        .arg("hook")
+        .write_stdin(POST_TOOL_USE)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_backend_config_syntax() {
+    let backend: BackendConfig = toml::from_str(
+        r#"
+        name = "desk"
+        type = "ntfy"
+        server_url = "https://ntfy.example.com"
+        topic = "desk-alerts"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(backend.name, "desk");
+    let BackendKind::Ntfy(ntfy) = backend.kind else {
+        panic!("Expected an ntfy backend");
+    };
+    assert_eq!(ntfy.topic.as_deref(), Some("desk-alerts"));
+    assert_eq!(ntfy.auth_token, None);
+}
+
+#[test]
+fn test_hook_fans_out_to_routed_backends() {
+    let phone = MockNtfyServer::start(None);
+    let desk = MockNtfyServer::start(None);
+    let project = project_with_backends(&phone.url, &desk.url, &["ntfy", "desk"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let phone_messages = phone.published();
+    let desk_messages = desk.published();
+    assert_eq!(phone_messages.len(), 1);
+    assert_eq!(desk_messages.len(), 1);
+    assert_eq!(phone_messages[0]["message"], desk_messages[0]["message"]);
+    assert_eq!(desk_messages[0]["topic"], "desk-alerts");
+}
+
+#[test]
+fn test_hook_only_uses_routed_backends() {
+    let phone = MockNtfyServer::start(None);
+    let desk = MockNtfyServer::start(None);
+    let project = project_with_backends(&phone.url, &desk.url, &["desk"]);
+
+    assert!(run_hook(&project).status.success());
+    assert!(phone.published().is_empty());
+    assert_eq!(desk.published().len(), 1);
+}
+
+#[test]
+fn test_unknown_backend_in_route_fails() {
+    let phone = MockNtfyServer::start(None);
+    let project = project_with_backends(&phone.url, &phone.url, &["pager"]);
+
+    let output = run_hook(&project);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown backend 'pager'"));
+    assert!(phone.published().is_empty());
+}
+
+#[test]
+fn test_webhook_posts_rendered_body() {
+    let ntfy = MockNtfyServer::start(None);
+    let server = MockHttpServer::start();
+    let body = r#"{"source": "claude", "summary": "{{title}}", "tool": "{{hook.tool_name}}", "file": {{json hook.tool_input.file_path}}}"#;
+    let project = project_with_backend(&ntfy.url, vec![webhook_backend(&server.url, Some(body))], &["incidents"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/events");
+    assert_eq!(requests[0].headers["authorization"], "Token secret");
+    assert_eq!(requests[0].headers["content-type"], "application/json");
+
+    let json = requests[0].json();
+    assert_eq!(json["source"], "claude");
+    assert_eq!(json["tool"], "Write");
+    assert_eq!(json["file"], "src/lib.rs");
+    assert_eq!(json["summary"], "Tool Completed");
+    assert!(ntfy.published().is_empty());
+}
+
+#[test]
+fn test_webhook_retries_failed_requests() {
+    let ntfy = MockNtfyServer::start(None);
+    let server = MockHttpServer::start_failing(1);
+    let project = project_with_backend(&ntfy.url, vec![webhook_backend(&server.url, None)], &["incidents"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].json()["hook_name"], "PostToolUse");
+    assert_eq!(requests[1].json()["hook"]["tool_name"], "Write");
+}
+
+#[test]
+fn test_slack_and_discord_backends() {
+    let ntfy = MockNtfyServer::start(None);
+    let slack = MockHttpServer::start();
+    let discord = MockHttpServer::start();
+
+    let backends = vec![chat_backend("slack", &slack.url), chat_backend("discord", &discord.url)];
+    let project = project_with_backend(&ntfy.url, backends, &["slack", "discord"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let slack_requests = slack.requests();
+    assert_eq!(slack_requests.len(), 1);
+    let blocks = &slack_requests[0].json()["attachments"][0]["blocks"];
+    assert_eq!(blocks[0]["text"]["text"], "Tool Completed");
+    assert_eq!(blocks[2]["fields"][0]["text"], "*Tool*\n`Write`");
+
+    let discord_requests = discord.requests();
+    assert_eq!(discord_requests.len(), 1);
+    let embed = &discord_requests[0].json()["embeds"][0];
+    assert_eq!(embed["title"], "Tool Completed");
+    assert_eq!(embed["fields"][0]["value"], "`Write`");
+}
+
+#[test]
+fn test_gotify_and_pushover_backends() {
+    let ntfy = MockNtfyServer::start(None);
+    let gotify = MockHttpServer::start();
+    let pushover = MockHttpServer::start_responding(r#"{"status": 1}"#);
+
+    let backends = vec![
+        BackendConfig {
+            name: "gotify".to_string(),
+            kind: BackendKind::Gotify(GotifyBackendConfig {
+                server_url: gotify.url.clone(),
+                app_token: "gotify-app".to_string(),
+                timeout_secs: Some(5),
+            }),
+        },
+        BackendConfig {
+            name: "pushover".to_string(),
+            kind: BackendKind::Pushover(PushoverBackendConfig {
+                app_token: "pushover-app".to_string(),
+                user_key: "pushover-user".to_string(),
+                device: None,
+                api_url: pushover.url.clone(),
+                emergency_retry_secs: 60,
+                emergency_expire_secs: 3600,
+                timeout_secs: Some(5),
+            }),
+        },
+    ];
+    let project = project_with_backend(&ntfy.url, backends, &["gotify", "pushover"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let gotify_requests = gotify.requests();
+    assert_eq!(gotify_requests.len(), 1);
+    assert_eq!(gotify_requests[0].path, "/message");
+    assert_eq!(gotify_requests[0].headers["x-gotify-key"], "gotify-app");
+    assert_eq!(gotify_requests[0].json()["priority"], 5);
+
+    let pushover_requests = pushover.requests();
+    assert_eq!(pushover_requests.len(), 1);
+    assert_eq!(pushover_requests[0].path, "/1/messages.json");
+    let form: HashMap<String, String> = url::form_urlencoded::parse(pushover_requests[0].body.as_bytes())
+        .into_owned()
+        .collect();
+    assert_eq!(form["token"], "pushover-app");
+    assert_eq!(form["user"], "pushover-user");
+    assert_eq!(form["priority"], "0");
+    assert_eq!(form["title"], "Tool Completed");
+}
+
+#[test]
+fn test_smtp_backend_sends_multipart_email() {
+    let ntfy = MockNtfyServer::start(None);
+    let smtp = MockSmtpServer::start();
+
+    let backends = vec![BackendConfig {
+        name: "email".to_string(),
+        kind: BackendKind::Smtp(SmtpBackendConfig {
+            host: "127.0.0.1".to_string(),
+            port: Some(smtp.port),
+            security: SmtpSecurity::Plain,
+            username: Some("claude".to_string()),
+            password: Some("hunter2".to_string()),
+            from: "Claude <claude@example.com>".to_string(),
+            to: vec!["dev@example.com".to_string()],
+            timeout_secs: Some(5),
+        }),
+    }];
+    let project = project_with_backend(&ntfy.url, backends, &["email"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let emails = smtp.emails();
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].from, "claude@example.com");
+    assert_eq!(emails[0].to, vec!["dev@example.com"]);
+    // AUTH PLAIN "\0claude\0hunter2"
+    assert_eq!(emails[0].auth, vec!["AGNsYXVkZQBodW50ZXIy"]);
+
+    let data = &emails[0].data;
+    assert!(data.contains("Subject: Tool Completed"));
+    assert!(data.contains("multipart/alternative"));
+    assert!(data.contains("Content-Type: text/plain"));
+    assert!(data.contains("Content-Type: text/html"));
+    assert!(ntfy.published().is_empty());
+}
+
+#[test]
+fn test_command_backend_receives_args_and_stdin() {
+    let ntfy = MockNtfyServer::start(None);
+    let output_dir = TempDir::new().unwrap();
+    let output = output_dir.path().join("hook");
+
+    let backends = vec![BackendConfig {
+        name: "chime".to_string(),
+        kind: BackendKind::Command(CommandBackendConfig {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                r#"cat > "$0.json" && printf '%s|%s' "$1" "$2" > "$0.args""#.to_string(),
+                output.display().to_string(),
+                "{{title}}".to_string(),
+                "{{priority}}".to_string(),
+            ],
+            timeout_secs: Some(5),
+            max_concurrent: 1,
+        }),
+    }];
+    let project = project_with_backend(&ntfy.url, backends, &["chime"]);
+
+    assert!(run_hook(&project).status.success());
+
+    let args = std::fs::read_to_string(output.with_extension("args")).unwrap();
+    assert_eq!(args, "Tool Completed|3");
+    let stdin: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(output.with_extension("json")).unwrap()).unwrap();
+    assert_eq!(stdin["tool_name"], "Write");
+    assert!(ntfy.published().is_empty());
+}
@@ -0,0 +1,740 @@
+//! Integration tests for delivering hooks through a running daemon
+
+mod mock_http;
+mod mock_ntfy;
+
+use assert_cmd::cargo::CommandCargoExt;
+use claude_ntfy::shared::config::{
+    BackendConfig, BackendKind, CommandBackendConfig, DaemonScope, WebhookBackendConfig,
+};
+use claude_ntfy::shared::templates::TemplateStyle;
+use claude_ntfy::Config;
+use mock_http::MockHttpServer;
+use mock_ntfy::MockNtfyServer;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+const POST_TOOL_USE: &str = r#"{"hook_event_name": "PostToolUse", "tool_name": "Edit", "tool_input": {"file_path": "src/main.rs"}}"#;
+
+/// A foreground daemon using a temporary home directory
+struct TestDaemon {
+    home: TempDir,
+    child: Child,
+}
+
+impl TestDaemon {
+    fn start() -> Self {
+        Self::start_with(|_| {})
+    }
+
+    /// Start a daemon whose own config is adjusted by `configure`
+    fn start_with(configure: impl FnOnce(&mut Config)) -> Self {
+        let home = TempDir::new().unwrap();
+        let mut config = Config::default();
+        configure(&mut config);
+        write_config(home.path(), &config);
+        Self::start_in(home)
+    }
+
+    /// Start a daemon in an existing home directory
+    fn start_in(home: TempDir) -> Self {
+        let child = Self::spawn(home.path());
+        let daemon = Self { home, child };
+        daemon.wait_for(|| daemon.socket_path().exists());
+        daemon
+    }
+
+    fn spawn(home: &Path) -> Child {
+        Command::cargo_bin("claude-ntfy")
+            .unwrap()
+            .args(["daemon", "start"])
+            .env("HOME", home)
+            .current_dir(home)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap()
+    }
+
+    /// Kill the daemon without a chance to clean up, then start it again
+    fn crash_and_restart(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        std::fs::remove_file(self.socket_path()).unwrap();
+
+        self.child = Self::spawn(self.home.path());
+        self.wait_for(|| self.socket_path().exists());
+    }
+
+    fn socket_path(&self) -> PathBuf {
+        self.home.path().join(".claude/ntfy-service/daemon.sock")
+    }
+
+    /// Run `claude-ntfy hook` for a project against this daemon
+    fn run_hook(&self, project: &Path, stdin: &str, extra_args: &[&str]) {
+        run_hook(self.home.path(), project, stdin, extra_args);
+    }
+
+    /// Run a `claude-ntfy` command against this daemon, returning its stdout
+    fn cli(&self, args: &[&str]) -> String {
+        let output = assert_cmd::Command::cargo_bin("claude-ntfy")
+            .unwrap()
+            .env("HOME", self.home.path())
+            .current_dir(self.home.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    /// Run a `claude-ntfy` command expected to fail, returning its stderr
+    fn cli_failure(&self, args: &[&str]) -> String {
+        let output = assert_cmd::Command::cargo_bin("claude-ntfy")
+            .unwrap()
+            .env("HOME", self.home.path())
+            .current_dir(self.home.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+        String::from_utf8(output.stderr).unwrap()
+    }
+
+    fn dead_letter_ids(&self) -> Vec<String> {
+        let output = self.cli(&["daemon", "dlq", "list"]);
+        output
+            .lines()
+            .filter(|line| *line != "No dead letters")
+            .map(|line| line.split_whitespace().next().unwrap().to_string())
+            .collect()
+    }
+
+    /// Poll until `condition` holds, failing after 10 seconds
+    fn wait_for(&self, condition: impl Fn() -> bool) {
+        wait_for(condition);
+    }
+}
+
+impl Drop for TestDaemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Run `claude-ntfy hook` for a project with the given home directory
+fn run_hook(home: &Path, project: &Path, stdin: &str, extra_args: &[&str]) {
+    let output = assert_cmd::Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .env("HOME", home)
+        .arg("--project")
+        .arg(project)
+        .arg("hook")
+        .args(extra_args)
+        .write_stdin(stdin)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+/// Poll until `condition` holds, failing after 10 seconds
+fn wait_for(condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition() {
+        assert!(Instant::now() < deadline, "Timed out waiting for the daemon");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+fn spool_entries(home: &Path) -> usize {
+    std::fs::read_dir(home.join(".claude/ntfy-service/spool")).map_or(0, |entries| entries.count())
+}
+
+/// Write a project config with the daemon enabled, letting `configure` adjust it
+fn project(ntfy_url: &str, configure: impl FnOnce(&mut Config)) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.ntfy.server_url = ntfy_url.to_string();
+    config.daemon.enabled = true;
+    configure(&mut config);
+    write_config(temp_dir.path(), &config);
+    temp_dir
+}
+
+/// Write a project config routing PostToolUse to a webhook only
+fn project_with_webhook(ntfy_url: &str, webhook_url: &str) -> TempDir {
+    project(ntfy_url, |config| {
+        config.backends = vec![BackendConfig {
+            name: "incidents".to_string(),
+            kind: BackendKind::Webhook(WebhookBackendConfig {
+                url: format!("{webhook_url}/events"),
+                headers: HashMap::new(),
+                body: None,
+                timeout_secs: Some(5),
+            }),
+        }];
+        config.hooks.routes.insert("PostToolUse".to_string(), vec!["incidents".to_string()]);
+    })
+}
+
+/// A `Submit` message for the JSON protocol, delivering to `backends`
+fn submit_message(project_path: Option<&Path>, backends: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "Submit": {
+            "hook_name": "PostToolUse",
+            "hook_data": POST_TOOL_USE,
+            "retry_count": 0,
+            "timestamp": "2026-01-01T12:00:00+00:00",
+            "ntfy_config": {
+                "server_url": "https://ntfy.sh",
+                "topic": "claude-notifications",
+                "priority": 3,
+                "tags": null,
+                "auth_token": null,
+                "timeout_secs": 5,
+                "custom_template": null,
+                "variables": {},
+                "retry_attempts": 0,
+                "retry_base_delay_ms": 0
+            },
+            "project_path": project_path,
+            "backends": backends
+        }
+    })
+}
+
+/// Send one message over the JSON protocol, returning the handshake reply and the response
+fn request_json(mut stream: impl Read + Write, hello: &str, message: &serde_json::Value) -> (String, String) {
+    stream.write_all(format!("{hello}\n\n{message}\n").as_bytes()).unwrap();
+
+    let mut lines = BufReader::new(stream).lines();
+    let greeting = lines.next().unwrap().unwrap();
+    let response = lines.next().map(Result::unwrap).unwrap_or_default();
+    (greeting, response)
+}
+
+fn write_config(dir: &Path, config: &Config) {
+    let config_dir = dir.join(".claude/ntfy-service");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), toml::to_string_pretty(config).unwrap()).unwrap();
+}
+
+#[test]
+fn test_daemon_renders_like_direct_delivery() {
+    let ntfy = MockNtfyServer::start(None);
+    let daemon = TestDaemon::start();
+    let project = project(&ntfy.url, |config| {
+        config.ntfy.default_topic = "team-alerts".to_string();
+        config.ntfy.default_tags = Some(vec!["robot".to_string()]);
+        config.ntfy.timeout_secs = Some(5);
+        config.templates.use_custom = true;
+        config.templates.variables = HashMap::from([("team".to_string(), "Platform".to_string())]);
+        config.templates.custom_templates.insert(
+            "PostToolUse".to_string(),
+            "[{{team}}] {{tool_name}} edited {{tool_input.file_path}}".to_string(),
+        );
+        config.templates.custom_templates.insert("Notification".to_string(), "[{{team}}] {{message}}".to_string());
+        config.templates.style = TemplateStyle::Compact;
+    });
+
+    let notification = r#"{"hook_event_name": "Notification", "message": "Claude needs your input"}"#;
+    for (i, stdin) in [POST_TOOL_USE, notification].into_iter().enumerate() {
+        daemon.run_hook(project.path(), stdin, &["--no-daemon"]);
+        daemon.run_hook(project.path(), stdin, &[]);
+        daemon.wait_for(|| ntfy.published().len() == 2 * (i + 1));
+    }
+
+    let published = ntfy.published();
+    assert_eq!(published[0]["message"], "[Platform] Edit edited src/main.rs");
+    assert_eq!(published[0]["tags"], serde_json::json!(["tool"]));
+    assert_eq!(published[2]["message"], "[Platform] Claude needs your input");
+    assert_eq!(published[2]["tags"], serde_json::json!(["robot"]));
+    for pair in published.chunks(2) {
+        assert_eq!(pair[0], pair[1], "daemon delivery should match direct delivery");
+    }
+}
+
+#[test]
+fn test_failing_backend_does_not_block_queue() {
+    let daemon = TestDaemon::start_with(|config| {
+        config.daemon.retry_attempts = 2;
+        config.daemon.retry_delay_secs = 2;
+    });
+
+    // Each daemon attempt makes four requests: one plus three client retries
+    let failing = MockHttpServer::start_failing(8);
+    let ntfy = MockNtfyServer::start(None);
+    let broken = project_with_webhook(&ntfy.url, &failing.url);
+    let healthy = project(&ntfy.url, |_| {});
+
+    daemon.run_hook(broken.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| failing.requests().len() == 4);
+
+    // Delivered while the failed task waits for its retry
+    daemon.run_hook(healthy.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| ntfy.published().len() == 1);
+    assert_eq!(failing.requests().len(), 4);
+
+    // Two scheduled retries, the last of which succeeds
+    daemon.wait_for(|| failing.requests().len() == 9);
+    assert_eq!(ntfy.published().len(), 1);
+}
+
+#[test]
+fn test_project_send_retries_apply_through_the_daemon() {
+    let daemon = TestDaemon::start_with(|config| config.daemon.retry_attempts = 0);
+    let ntfy = MockNtfyServer::start(None);
+    let broken = MockHttpServer::start_failing(usize::MAX);
+    let project = project_with_webhook(&ntfy.url, &broken.url);
+    let config_path = project.path().join(".claude/ntfy-service/config.toml");
+    let mut config: Config = toml::from_str(&std::fs::read_to_string(config_path).unwrap()).unwrap();
+    config.retry.attempts = 1;
+    config.retry.base_delay_ms = 10;
+    write_config(project.path(), &config);
+
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| daemon.dead_letter_ids().len() == 1);
+    assert_eq!(broken.requests().len(), 2);
+}
+
+#[test]
+fn test_sessions_deliver_concurrently_and_in_order() {
+    let daemon = TestDaemon::start();
+    let ntfy = MockNtfyServer::start(None);
+    let log = daemon.home.path().join("delivered.log");
+    let project = project(&ntfy.url, |config| {
+        config.backends = vec![BackendConfig {
+            name: "log".to_string(),
+            kind: BackendKind::Command(CommandBackendConfig {
+                command: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    format!(r#"[ "$1" = Slow ] && sleep 1; echo "$1" >> '{}'"#, log.display()),
+                    "sh".to_string(),
+                    "{{hook.tool_name}}".to_string(),
+                ],
+                timeout_secs: Some(10),
+                max_concurrent: 4,
+            }),
+        }];
+        config.hooks.routes.insert("PostToolUse".to_string(), vec!["log".to_string()]);
+    });
+    let hook = |session: &str, tool: &str| {
+        format!(r#"{{"hook_event_name": "PostToolUse", "session_id": "{session}", "tool_name": "{tool}"}}"#)
+    };
+
+    daemon.run_hook(project.path(), &hook("a", "Slow"), &[]);
+    daemon.run_hook(project.path(), &hook("a", "Fast"), &[]);
+    daemon.run_hook(project.path(), &hook("b", "Other"), &[]);
+
+    // Session b does not wait for session a, whose tasks keep their order
+    let delivered = || std::fs::read_to_string(&log).unwrap_or_default();
+    daemon.wait_for(|| delivered().lines().count() == 3);
+    assert_eq!(delivered().lines().collect::<Vec<_>>(), vec!["Other", "Slow", "Fast"]);
+}
+
+#[test]
+fn test_spooled_task_survives_crash() {
+    let mut daemon = TestDaemon::start_with(|config| {
+        config.daemon.retry_delay_secs = 300;
+    });
+    let failing = MockHttpServer::start_failing(4);
+    let ntfy = MockNtfyServer::start(None);
+    let project = project_with_webhook(&ntfy.url, &failing.url);
+
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| failing.requests().len() == 4);
+
+    // The pending retry is replayed as soon as the daemon comes back
+    daemon.crash_and_restart();
+    daemon.wait_for(|| failing.requests().len() == 5);
+    assert_eq!(failing.requests()[4].json()["hook_name"], "PostToolUse");
+
+    daemon.wait_for(|| spool_entries(daemon.home.path()) == 0);
+}
+
+#[test]
+fn test_hook_spools_while_daemon_is_down() {
+    let ntfy = MockNtfyServer::start(None);
+    let home = TempDir::new().unwrap();
+    let project = project(&ntfy.url, |_| {});
+
+    run_hook(home.path(), project.path(), POST_TOOL_USE, &[]);
+    assert_eq!(spool_entries(home.path()), 1);
+    assert!(ntfy.published().is_empty());
+
+    let daemon = TestDaemon::start_in(home);
+    daemon.wait_for(|| ntfy.published().len() == 1);
+    assert_eq!(ntfy.published()[0]["title"], "Tool Completed");
+    daemon.wait_for(|| spool_entries(daemon.home.path()) == 0);
+}
+
+#[test]
+fn test_hook_autostarts_daemon() {
+    let ntfy = MockNtfyServer::start(None);
+    let home = TempDir::new().unwrap();
+    write_config(home.path(), &Config::default());
+    let project = project(&ntfy.url, |config| config.daemon.autostart = true);
+
+    run_hook(home.path(), project.path(), POST_TOOL_USE, &[]);
+    let pid_file = home.path().join(".claude/ntfy-service/daemon.pid");
+    wait_for(|| pid_file.exists());
+
+    // Stop the autostarted daemon even if the assertions below fail
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let _stop = scopeguard::guard(pid, |pid| {
+        let _ = Command::new("kill").arg(pid.trim()).status();
+    });
+
+    wait_for(|| ntfy.published().len() == 1);
+    assert!(!home.path().join(".claude/ntfy-service/daemon.starting").exists());
+}
+
+#[test]
+fn test_dead_letter_queue() {
+    let daemon = TestDaemon::start_with(|config| config.daemon.retry_attempts = 0);
+    let ntfy = MockNtfyServer::start(None);
+
+    // Each daemon attempt makes four requests: one plus three client retries
+    let flaky = MockHttpServer::start_failing(8);
+    let flaky_project = project_with_webhook(&ntfy.url, &flaky.url);
+    daemon.run_hook(flaky_project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| daemon.dead_letter_ids().len() == 1);
+
+    let id = daemon.dead_letter_ids().remove(0);
+    let shown = daemon.cli(&["daemon", "dlq", "show", &id]);
+    assert!(shown.contains("Hook:     PostToolUse"), "{shown}");
+    assert!(shown.contains("[incidents]"), "{shown}");
+    assert!(shown.contains("\"tool_name\": \"Edit\""), "{shown}");
+
+    // Fails again and comes back with both attempts recorded
+    assert!(daemon.cli(&["daemon", "dlq", "retry", "--all"]).contains("Queued 1"));
+    daemon.wait_for(|| flaky.requests().len() == 8 && daemon.dead_letter_ids() == [id.clone()]);
+    assert_eq!(daemon.cli(&["daemon", "dlq", "show", &id]).matches("[incidents]").count(), 2);
+
+    daemon.cli(&["daemon", "dlq", "retry", &id]);
+    daemon.wait_for(|| flaky.requests().len() == 9);
+    daemon.wait_for(|| daemon.dead_letter_ids().is_empty());
+
+    let broken = MockHttpServer::start_failing(usize::MAX);
+    let broken_project = project_with_webhook(&ntfy.url, &broken.url);
+    daemon.run_hook(broken_project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| daemon.dead_letter_ids().len() == 1);
+    assert!(daemon.cli(&["daemon", "dlq", "purge", "--all"]).contains("Purged 1"));
+    assert!(daemon.dead_letter_ids().is_empty());
+    assert!(ntfy.published().is_empty());
+}
+
+#[test]
+fn test_reload_applies_valid_config_only() {
+    let daemon = TestDaemon::start();
+    let config_path = daemon.home.path().join(".claude/ntfy-service/config.toml");
+
+    // An invalid file is rejected, leaving the daemon running
+    std::fs::write(&config_path, "[daemon\nretry_attempts = 0").unwrap();
+    Command::new("kill")
+        .args(["-HUP", &daemon.child.id().to_string()])
+        .status()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+
+    let mut config = Config::default();
+    config.daemon.log_level = "verbose".to_string();
+    write_config(daemon.home.path(), &config);
+    assert!(daemon.cli(&["daemon", "status"]).contains("Daemon is running"));
+    let error = daemon.cli_failure(&["daemon", "reload"]);
+    assert!(error.contains("Invalid daemon.log_level 'verbose'"), "{error}");
+
+    // Without daemon-level retries a failing task is dead-lettered at once
+    config.daemon.log_level = "debug".to_string();
+    config.daemon.retry_attempts = 0;
+    write_config(daemon.home.path(), &config);
+    assert!(daemon.cli(&["daemon", "reload"]).contains("reload signal sent"));
+
+    let ntfy = MockNtfyServer::start(None);
+    let failing = MockHttpServer::start_failing(usize::MAX);
+    let project = project_with_webhook(&ntfy.url, &failing.url);
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| daemon.dead_letter_ids().len() == 1);
+    assert_eq!(failing.requests().len(), 4);
+}
+
+#[test]
+fn test_reload_changes_where_tasks_are_delivered() {
+    let before = MockNtfyServer::start(None);
+    let after = MockNtfyServer::start(None);
+    let daemon = TestDaemon::start_with(|config| config.ntfy.server_url = before.url.clone());
+
+    let mut config = Config::default();
+    config.ntfy.server_url = after.url.clone();
+    write_config(daemon.home.path(), &config);
+    assert!(daemon.cli(&["daemon", "reload"]).contains("reload signal sent"));
+
+    // A task without a project is delivered with the daemon's own backends
+    let socket = std::os::unix::net::UnixStream::connect(daemon.socket_path()).unwrap();
+    let (_, response) = request_json(socket, "CNTF/1 json", &submit_message(None, &["ntfy"]));
+    assert_eq!(response, "\"Ok\"");
+    wait_for(|| after.published().len() == 1);
+    assert!(before.published().is_empty());
+}
+
+#[test]
+fn test_watch_config_reloads_on_change() {
+    let daemon = TestDaemon::start_with(|config| config.daemon.watch_config = true);
+    let mut config = Config::default();
+    config.daemon.watch_config = true;
+    config.daemon.retry_attempts = 0;
+    write_config(daemon.home.path(), &config);
+    std::thread::sleep(Duration::from_millis(2500));
+
+    let ntfy = MockNtfyServer::start(None);
+    let failing = MockHttpServer::start_failing(usize::MAX);
+    let project = project_with_webhook(&ntfy.url, &failing.url);
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| daemon.dead_letter_ids().len() == 1);
+    assert_eq!(failing.requests().len(), 4);
+}
+
+#[test]
+fn test_status_reports_delivery_statistics() {
+    let daemon = TestDaemon::start();
+    let ntfy = MockNtfyServer::start(None);
+    let project = project(&ntfy.url, |_| {});
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| ntfy.published().len() == 1);
+
+    let status: serde_json::Value = serde_json::from_str(&daemon.cli(&["daemon", "status", "--json"])).unwrap();
+    assert_eq!(status["is_running"], true);
+    assert_eq!(status["queue_size"], 0);
+    assert_eq!(status["hooks"][0]["name"], "PostToolUse");
+    assert_eq!(status["hooks"][0]["sent"], 1);
+    assert_eq!(status["backends"][0]["name"], "ntfy");
+    assert!(status["backends"][0]["latency"]["p99_ms"].is_u64());
+    let project_name = status["projects"][0]["name"].as_str().unwrap();
+    assert!(project_name.ends_with(project.path().file_name().unwrap().to_str().unwrap()));
+
+    let text = daemon.cli(&["daemon", "status"]);
+    assert!(text.contains("Backends"), "{text}");
+    assert!(text.contains("PostToolUse"), "{text}");
+}
+
+#[test]
+fn test_tail_streams_filtered_events() {
+    let daemon = TestDaemon::start();
+    let ntfy = MockNtfyServer::start(None);
+    let project = project(&ntfy.url, |_| {});
+
+    let mut tail = Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .args(["tail", "--json", "--hook", "PostToolUse"])
+        .env("HOME", daemon.home.path())
+        .current_dir(daemon.home.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The banner is printed once the subscription is acknowledged
+    let mut banner = String::new();
+    BufReader::new(tail.stderr.take().unwrap()).read_line(&mut banner).unwrap();
+    assert!(banner.starts_with("Waiting for daemon events"), "{banner}");
+
+    daemon.run_hook(project.path(), r#"{"hook_event_name": "Stop"}"#, &[]);
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    daemon.wait_for(|| ntfy.published().len() == 2);
+
+    let mut events = BufReader::new(tail.stdout.take().unwrap()).lines();
+    let mut kinds = Vec::new();
+    while kinds.last().map(String::as_str) != Some("sent") {
+        let event: serde_json::Value = serde_json::from_str(&events.next().unwrap().unwrap()).unwrap();
+        assert_eq!(event["hook_name"], "PostToolUse");
+        let kind = match &event["kind"] {
+            serde_json::Value::String(kind) => kind.clone(),
+            kind => kind.as_object().unwrap().keys().next().unwrap().clone(),
+        };
+        kinds.push(kind);
+    }
+    let _ = tail.kill();
+    let _ = tail.wait();
+
+    assert_eq!(kinds, vec!["received", "rendered", "sent"]);
+}
+
+#[test]
+fn test_json_protocol_for_debugging_by_hand() {
+    let daemon = TestDaemon::start();
+    let mut socket = std::os::unix::net::UnixStream::connect(daemon.socket_path()).unwrap();
+    socket.write_all(b"CNTF/1 json\n\n\"Status\"\n").unwrap();
+
+    let mut lines = BufReader::new(socket).lines();
+    let greeting = lines.next().unwrap().unwrap();
+    assert!(greeting.starts_with("CNTF/1 ok "), "{greeting}");
+    assert!(greeting.split_whitespace().any(|capability| capability == "subscribe"), "{greeting}");
+
+    let response: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(response["Status"]["is_running"], true);
+
+    // A client of another protocol version is told so
+    let mut socket = std::os::unix::net::UnixStream::connect(daemon.socket_path()).unwrap();
+    socket.write_all(b"CNTF/99 bincode\n").unwrap();
+    let mut reply = String::new();
+    BufReader::new(socket).read_line(&mut reply).unwrap();
+    assert_eq!(reply, "CNTF/1 error unsupported protocol version 99, the daemon speaks 1\n");
+}
+
+#[test]
+fn test_remote_hooks_deliver_through_tls_listener() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls");
+    let ntfy = MockNtfyServer::start(None);
+    let daemon = TestDaemon::start_with(|config| {
+        config.ntfy.server_url = ntfy.url.clone();
+        config.daemon.listen_address = Some(format!("127.0.0.1:{port}"));
+        config.daemon.auth_token = Some("s3cret".to_string());
+        config.daemon.tls_cert = Some(fixtures.join("cert.pem"));
+        config.daemon.tls_key = Some(fixtures.join("key.pem"));
+    });
+    daemon.wait_for(|| std::net::TcpStream::connect(("127.0.0.1", port)).is_ok());
+
+    // A devcontainer with its own home directory and no daemon of its own
+    let container = TempDir::new().unwrap();
+    let remote_project = |token: &str| {
+        project("http://127.0.0.1:9", |config| {
+            config.daemon.remote_address = Some(format!("tls://localhost:{port}"));
+            config.daemon.remote_ca_cert = Some(fixtures.join("ca.pem"));
+            config.daemon.auth_token = Some(token.to_string());
+        })
+    };
+
+    let project = remote_project("s3cret");
+    run_hook(container.path(), project.path(), POST_TOOL_USE, &[]);
+    wait_for(|| ntfy.published().len() == 1);
+    assert_eq!(spool_entries(container.path()), 0);
+
+    // A wrong token is refused, so the hook falls back to spooling locally
+    let project = remote_project("guess");
+    run_hook(container.path(), project.path(), POST_TOOL_USE, &[]);
+    assert_eq!(spool_entries(container.path()), 1);
+    assert_eq!(ntfy.published().len(), 1);
+}
+
+#[test]
+fn test_remote_clients_only_submit_to_the_daemon_backends() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let ntfy = MockNtfyServer::start(None);
+    let daemon = TestDaemon::start_with(|config| {
+        config.ntfy.server_url = ntfy.url.clone();
+        config.daemon.listen_address = Some(format!("127.0.0.1:{port}"));
+        config.daemon.auth_token = Some("s3cret".to_string());
+    });
+    daemon.wait_for(|| std::net::TcpStream::connect(("127.0.0.1", port)).is_ok());
+    let remote = |message: &serde_json::Value| {
+        let stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        request_json(stream, "CNTF/1 json token=s3cret", message)
+    };
+
+    // Only submitting and pinging are offered, and nothing else is handled
+    let (greeting, response) = remote(&serde_json::json!("Shutdown"));
+    assert_eq!(greeting, "CNTF/1 ok submit ping");
+    assert!(response.contains("'shutdown' is not allowed"), "{response}");
+    assert!(daemon.cli(&["daemon", "status"]).contains("Daemon is running"));
+
+    // Neither a project's backends nor backend definitions sent along are used
+    let marker = daemon.home.path().join("ran");
+    let command = BackendConfig {
+        name: "shell".to_string(),
+        kind: BackendKind::Command(CommandBackendConfig {
+            command: "touch".to_string(),
+            args: vec![marker.display().to_string()],
+            timeout_secs: Some(10),
+            max_concurrent: 1,
+        }),
+    };
+    let project = project(&ntfy.url, |config| config.backends = vec![command.clone()]);
+    assert_eq!(remote(&submit_message(Some(project.path()), &["shell"])).1, "\"Ok\"");
+    daemon.wait_for(|| daemon.dead_letter_ids().len() == 1);
+    let mut definitions = submit_message(None, &[]);
+    definitions["Submit"]["backends"] = serde_json::json!([command]);
+    assert!(remote(&definitions).1.contains("Error"));
+    assert!(!marker.exists());
+
+    // The daemon's own backends deliver remote tasks
+    assert_eq!(remote(&submit_message(Some(project.path()), &["ntfy"])).1, "\"Ok\"");
+    wait_for(|| ntfy.published().len() == 1);
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_daemon_files_are_private_and_kept_from_a_second_daemon() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let daemon = TestDaemon::start();
+    let mode = |path: PathBuf| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(daemon.socket_path()), 0o600);
+    assert_eq!(mode(daemon.socket_path().with_extension("pid")), 0o600);
+    let pid = std::fs::read_to_string(daemon.socket_path().with_extension("pid")).unwrap();
+
+    // A second daemon racing past the PID check must not take over the socket
+    let output = assert_cmd::Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .args(["daemon", "start"])
+        .env("HOME", daemon.home.path())
+        .env("CLAUDE_DAEMON_DETACHED", "1")
+        .current_dir(daemon.home.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already listening"));
+    assert_eq!(std::fs::read_to_string(daemon.socket_path().with_extension("pid")).unwrap(), pid);
+    assert!(daemon.cli(&["daemon", "status"]).contains("running"));
+}
+
+#[test]
+fn test_project_scope_runs_a_daemon_per_project() {
+    let daemon = TestDaemon::start();
+    let ntfy = MockNtfyServer::start(None);
+    let project = project(&ntfy.url, |config| config.daemon.scope = DaemonScope::Project);
+
+    let mut project_daemon = Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .env("HOME", daemon.home.path())
+        .arg("--project")
+        .arg(project.path())
+        .args(["daemon", "start"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let _stop = scopeguard::guard((), |_| {
+        let _ = project_daemon.kill();
+        let _ = project_daemon.wait();
+    });
+    let project_socket = project.path().join(".claude/ntfy-service/daemon.sock");
+    wait_for(|| project_socket.exists());
+
+    // Hooks and the CLI resolve the project's own daemon, not the global one
+    daemon.run_hook(project.path(), POST_TOOL_USE, &[]);
+    wait_for(|| ntfy.published().len() == 1);
+    let project_status = daemon.cli(&["--project", project.path().to_str().unwrap(), "daemon", "status", "--json"]);
+    let project_status: serde_json::Value = serde_json::from_str(&project_status).unwrap();
+    assert_eq!(project_status["hooks"][0]["sent"], 1);
+    let global_status: serde_json::Value = serde_json::from_str(&daemon.cli(&["daemon", "status", "--json"])).unwrap();
+    assert_eq!(global_status["is_running"], true);
+    assert!(global_status["hooks"].as_array().unwrap().is_empty());
+
+    let all: serde_json::Value = serde_json::from_str(&daemon.cli(&["daemon", "status", "--all", "--json"])).unwrap();
+    let all = all.as_array().unwrap();
+    assert_eq!(all.len(), 2);
+    let projects: Vec<_> = all.iter().map(|status| status["project_path"].clone()).collect();
+    assert!(projects.contains(&serde_json::Value::Null));
+    assert!(projects.contains(&serde_json::json!(project.path())));
+
+    let text = daemon.cli(&["daemon", "status", "--all"]);
+    assert!(text.contains("Global daemon"), "{text}");
+    assert!(text.contains(&format!("Project daemon for {}", project.path().display())), "{text}");
+}
@@ -51,7 +51,7 @@ fn test_config_show() {
     env.command()
         .args(["config", "show"])
         .expect_success()
-        .stdout_contains_all(&["[ntfy]", "server_url"])
+        .stdout_contains_all(["[ntfy]", "server_url"])
         .done();
 }
 
@@ -0,0 +1,126 @@
+//! Minimal recording HTTP server for backend integration tests
+//!
+//! Accepts any request, records its method, path, headers and body, and
+//! answers with a configurable status code and JSON body.
+
+// Each test crate uses a different subset of the helpers
+#![allow(dead_code)]
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A request received by the mock server
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Parse the body as JSON
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).expect("Request body should be JSON")
+    }
+}
+
+/// Local HTTP server running on a background thread
+pub struct MockHttpServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockHttpServer {
+    /// Start a server that answers every request with `200 {}`
+    pub fn start() -> Self {
+        Self::start_with(0, "{}")
+    }
+
+    /// Start a server that fails the first `failures` requests with a 500
+    pub fn start_failing(failures: usize) -> Self {
+        Self::start_with(failures, "{}")
+    }
+
+    /// Start a server that answers every request with `200` and `body`
+    pub fn start_responding(body: &str) -> Self {
+        Self::start_with(0, body)
+    }
+
+    fn start_with(failures: usize, body: &str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock HTTP server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let remaining_failures = Arc::new(AtomicUsize::new(failures));
+        let body = body.to_string();
+
+        let requests_clone = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let failing = remaining_failures
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_ok();
+                handle(stream, &requests_clone, failing, &body);
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn handle(mut stream: TcpStream, requests: &Mutex<Vec<RecordedRequest>>, failing: bool, body: &str) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() || line == "\r\n" || line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut request_body = vec![0u8; content_length];
+    let _ = reader.read_exact(&mut request_body);
+
+    requests.lock().unwrap().push(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&request_body).to_string(),
+    });
+
+    let (status, body) = if failing {
+        ("500 Internal Server Error", "{}")
+    } else {
+        ("200 OK", body)
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
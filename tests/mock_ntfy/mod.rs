@@ -0,0 +1,158 @@
+//! Minimal local ntfy stand-in for integration tests
+//!
+//! Supports publishing JSON messages to `/`, publishing plain text to
+//! `/<topic>`, and streaming subscriptions on `/<topic>/json`. Published
+//! messages are recorded so tests can assert on them, and the server can
+//! "press" an action button or type a reply on every published message to
+//! simulate a user.
+
+// Each test crate uses a different subset of the helpers
+#![allow(dead_code)]
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+type Subscribers = Arc<Mutex<HashMap<String, Vec<Sender<String>>>>>;
+
+/// Local ntfy server running on a background thread
+pub struct MockNtfyServer {
+    pub url: String,
+    published: Arc<Mutex<Vec<Value>>>,
+}
+
+/// Simulated user response to published messages
+#[derive(Clone)]
+enum UserAction {
+    /// Press the http action with this label
+    Press(String),
+    /// Type this text into the topic behind the "Reply" view action
+    Reply(String),
+}
+
+impl MockNtfyServer {
+    /// Start a server that presses the action labelled `press` on every published message
+    pub fn start(press: Option<&str>) -> Self {
+        Self::start_with(press.map(|label| UserAction::Press(label.to_string())))
+    }
+
+    /// Start a server that answers every message carrying a "Reply" action with `text`
+    pub fn start_replying(text: &str) -> Self {
+        Self::start_with(Some(UserAction::Reply(text.to_string())))
+    }
+
+    fn start_with(action: Option<UserAction>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock ntfy server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let subscribers: Subscribers = Arc::new(Mutex::new(HashMap::new()));
+
+        let published_clone = published.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let published = published_clone.clone();
+                let subscribers = subscribers.clone();
+                let action = action.clone();
+                thread::spawn(move || handle(stream, published, subscribers, action));
+            }
+        });
+
+        Self { url, published }
+    }
+
+    /// Messages published to the server so far
+    pub fn published(&self) -> Vec<Value> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    published: Arc<Mutex<Vec<Value>>>,
+    subscribers: Subscribers,
+    action: Option<UserAction>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() || line == "\r\n" || line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    let _ = reader.read_exact(&mut body);
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    if method == "GET" && path.ends_with("/json") {
+        let topic = path.trim_start_matches('/').trim_end_matches("/json").to_string();
+        let (sender, receiver) = mpsc::channel();
+        subscribers.lock().unwrap().entry(topic).or_default().push(sender);
+
+        let _ = stream.write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\r\n",
+        );
+        write_chunk(&mut stream, r#"{"event":"open"}"#);
+        for message in receiver {
+            let event = serde_json::json!({"event": "message", "message": message});
+            if !write_chunk(&mut stream, &event.to_string()) {
+                break;
+            }
+        }
+        return;
+    }
+
+    if method == "POST" && path == "/" {
+        let message: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+        published.lock().unwrap().push(message.clone());
+
+        let (label, reply) = match &action {
+            Some(UserAction::Press(label)) => (label.as_str(), None),
+            Some(UserAction::Reply(text)) => ("Reply", Some(text.as_str())),
+            None => ("", None),
+        };
+        let pressed = message["actions"]
+            .as_array()
+            .and_then(|actions| actions.iter().find(|a| a["label"] == label).cloned());
+        if let Some(pressed) = pressed {
+            let topic = pressed["url"].as_str().unwrap_or_default().rsplit('/').next().unwrap_or_default();
+            let body = reply.unwrap_or_else(|| pressed["body"].as_str().unwrap_or_default());
+            broadcast(&subscribers, topic, body);
+        }
+    } else if method == "POST" {
+        broadcast(&subscribers, path.trim_start_matches('/'), &body);
+    }
+
+    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}");
+}
+
+fn broadcast(subscribers: &Subscribers, topic: &str, message: &str) {
+    if let Some(senders) = subscribers.lock().unwrap().get(topic) {
+        for sender in senders {
+            let _ = sender.send(message.to_string());
+        }
+    }
+}
+
+fn write_chunk(stream: &mut TcpStream, line: &str) -> bool {
+    let data = format!("{line}\n");
+    let chunk = format!("{:x}\r\n{}\r\n", data.len(), data);
+    stream.write_all(chunk.as_bytes()).and_then(|_| stream.flush()).is_ok()
+}
@@ -0,0 +1,125 @@
+//! Minimal local SMTP sink for email backend integration tests
+//!
+//! Speaks just enough unencrypted ESMTP for a client to authenticate and
+//! deliver: EHLO, AUTH PLAIN/LOGIN, MAIL, RCPT, DATA, RSET and QUIT.
+//! Every delivered message is recorded with its envelope and credentials.
+
+// Each test crate uses a different subset of the helpers
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A message delivered to the sink
+#[derive(Debug, Clone, Default)]
+pub struct ReceivedEmail {
+    pub from: String,
+    pub to: Vec<String>,
+    /// Base64 `AUTH` arguments as sent by the client
+    pub auth: Vec<String>,
+    pub data: String,
+}
+
+/// Local SMTP server running on a background thread
+pub struct MockSmtpServer {
+    pub port: u16,
+    emails: Arc<Mutex<Vec<ReceivedEmail>>>,
+}
+
+impl MockSmtpServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock SMTP server");
+        let port = listener.local_addr().unwrap().port();
+        let emails = Arc::new(Mutex::new(Vec::new()));
+
+        let emails_clone = emails.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let emails = emails_clone.clone();
+                thread::spawn(move || handle(stream, &emails));
+            }
+        });
+
+        Self { port, emails }
+    }
+
+    /// Messages delivered so far
+    pub fn emails(&self) -> Vec<ReceivedEmail> {
+        self.emails.lock().unwrap().clone()
+    }
+}
+
+fn handle(mut stream: TcpStream, emails: &Mutex<Vec<ReceivedEmail>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let reply = |stream: &mut TcpStream, line: &str| {
+        let _ = stream.write_all(format!("{line}\r\n").as_bytes());
+    };
+    reply(&mut stream, "220 localhost mock ESMTP");
+
+    let mut email = ReceivedEmail::default();
+    let mut auth = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let line = line.trim_end();
+        let command = line.split_whitespace().next().unwrap_or_default().to_uppercase();
+
+        match command.as_str() {
+            "EHLO" | "HELO" => reply(&mut stream, "250-localhost\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME"),
+            "AUTH" => {
+                auth.extend(line.split_whitespace().skip(2).map(str::to_string));
+                if line.split_whitespace().nth(1).is_some_and(|m| m.eq_ignore_ascii_case("LOGIN")) {
+                    // Username and password prompts
+                    for prompt in ["334 VXNlcm5hbWU6", "334 UGFzc3dvcmQ6"] {
+                        reply(&mut stream, prompt);
+                        let mut answer = String::new();
+                        let _ = reader.read_line(&mut answer);
+                        auth.push(answer.trim_end().to_string());
+                    }
+                }
+                reply(&mut stream, "235 Authentication succeeded");
+            }
+            "MAIL" => {
+                email.from = address(line);
+                reply(&mut stream, "250 OK");
+            }
+            "RCPT" => {
+                email.to.push(address(line));
+                reply(&mut stream, "250 OK");
+            }
+            "DATA" => {
+                reply(&mut stream, "354 End data with <CR><LF>.<CR><LF>");
+                let mut data = String::new();
+                loop {
+                    let mut data_line = String::new();
+                    if reader.read_line(&mut data_line).unwrap_or(0) == 0 || data_line == ".\r\n" {
+                        break;
+                    }
+                    data.push_str(data_line.strip_prefix('.').unwrap_or(&data_line));
+                }
+                email.data = data;
+                email.auth = auth.clone();
+                emails.lock().unwrap().push(std::mem::take(&mut email));
+                reply(&mut stream, "250 OK: queued");
+            }
+            "RSET" | "NOOP" => reply(&mut stream, "250 OK"),
+            "QUIT" => {
+                reply(&mut stream, "221 Bye");
+                return;
+            }
+            _ => reply(&mut stream, "502 Command not implemented"),
+        }
+    }
+}
+
+/// Extract the address from `MAIL FROM:<a@b>` or `RCPT TO:<a@b>`
+fn address(line: &str) -> String {
+    line.split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(address, _)| address.to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,169 @@
+//! Minimal local Telegram Bot API stand-in for integration tests
+//!
+//! Supports `sendMessage`, `getUpdates` and `answerCallbackQuery`. Sent
+//! messages are recorded, and the server can press an inline keyboard button
+//! or send a text message in reply to every sent message to simulate a user.
+
+// Each test crate uses a different subset of the helpers
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const CHAT_ID: i64 = 4242;
+
+/// Simulated user response to sent messages
+#[derive(Clone)]
+enum UserAction {
+    /// Press the inline button with this label
+    Press(String),
+    /// Send this text to the chat
+    Reply(String),
+}
+
+#[derive(Default)]
+struct State {
+    sent: Vec<Value>,
+    updates: Vec<Value>,
+    next_update_id: i64,
+}
+
+/// Local Telegram server running on a background thread
+pub struct MockTelegramServer {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTelegramServer {
+    /// Start a server that presses the button labelled `press` on every sent message
+    pub fn start(press: Option<&str>) -> Self {
+        Self::start_with(press.map(|label| UserAction::Press(label.to_string())))
+    }
+
+    /// Start a server that answers every sent message with `text`
+    pub fn start_replying(text: &str) -> Self {
+        Self::start_with(Some(UserAction::Reply(text.to_string())))
+    }
+
+    fn start_with(action: Option<UserAction>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock Telegram server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State {
+            next_update_id: 100,
+            ..Default::default()
+        }));
+
+        // A stale update from before the test, which must be ignored
+        push_update(&mut state.lock().unwrap(), json!({"message": {"text": "stale", "chat": {"id": CHAT_ID}}}));
+
+        let state_clone = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = state_clone.clone();
+                let action = action.clone();
+                thread::spawn(move || handle(stream, &state, action));
+            }
+        });
+
+        Self { url, state }
+    }
+
+    /// Messages sent through `sendMessage` so far
+    pub fn sent(&self) -> Vec<Value> {
+        self.state.lock().unwrap().sent.clone()
+    }
+}
+
+fn push_update(state: &mut State, mut update: Value) {
+    update["update_id"] = json!(state.next_update_id);
+    state.next_update_id += 1;
+    state.updates.push(update);
+}
+
+fn handle(mut stream: TcpStream, state: &Mutex<State>, action: Option<UserAction>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).is_err() || line == "\r\n" || line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0u8; content_length];
+    let _ = reader.read_exact(&mut body);
+    let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    let method = path.rsplit('/').next().unwrap_or_default();
+    let result = match method {
+        "sendMessage" => {
+            let mut state = state.lock().unwrap();
+            state.sent.push(request.clone());
+            match &action {
+                Some(UserAction::Press(label)) => {
+                    let button = request["reply_markup"]["inline_keyboard"][0]
+                        .as_array()
+                        .and_then(|row| row.iter().find(|b| b["text"] == label.as_str()).cloned());
+                    if let Some(button) = button {
+                        push_update(&mut state, json!({"callback_query": {
+                            "id": "callback-1",
+                            "data": button["callback_data"],
+                            "message": {"chat": {"id": CHAT_ID}},
+                        }}));
+                    }
+                }
+                Some(UserAction::Reply(text)) => {
+                    push_update(&mut state, json!({"message": {"text": text, "chat": {"id": CHAT_ID}}}));
+                }
+                None => {}
+            }
+            json!({"message_id": state.sent.len()})
+        }
+        "getUpdates" => {
+            let offset = request["offset"].as_i64().unwrap_or(0);
+            let updates = {
+                let state = state.lock().unwrap();
+                if offset < 0 {
+                    state.updates.last().cloned().into_iter().collect()
+                } else {
+                    state
+                        .updates
+                        .iter()
+                        .filter(|u| u["update_id"].as_i64().unwrap() >= offset)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                }
+            };
+            if updates.is_empty() && request["timeout"].as_u64().unwrap_or(0) > 0 {
+                // Stand-in for long polling
+                thread::sleep(Duration::from_millis(200));
+            }
+            json!(updates)
+        }
+        _ => json!(true),
+    };
+
+    let response = json!({"ok": true, "result": result}).to_string();
+    let _ = stream.write_all(
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.len(),
+            response
+        )
+        .as_bytes(),
+    );
+}
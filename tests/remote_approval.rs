@@ -0,0 +1,107 @@
+//! Integration tests for remote PreToolUse approval via ntfy action buttons
+
+mod mock_ntfy;
+
+use assert_cmd::Command;
+use claude_ntfy::Config;
+use mock_ntfy::MockNtfyServer;
+use tempfile::TempDir;
+
+const PRE_TOOL_USE: &str = r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash", "tool_input": {"command": "rm -rf build"}}"#;
+
+/// Write a project config pointing at the mock server with approval enabled
+fn project_with_approval(server_url: &str, timeout_secs: u64) -> TempDir {
+    project_with(server_url, |config| config.approval.timeout_secs = timeout_secs)
+}
+
+/// Write a project config with approval enabled, letting `configure` adjust it
+fn project_with(server_url: &str, configure: impl FnOnce(&mut Config)) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.ntfy.server_url = server_url.to_string();
+    config.approval.enabled = true;
+    config.approval.timeout_secs = 10;
+    configure(&mut config);
+
+    let config_dir = temp_dir.path().join(".claude/ntfy-service");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), toml::to_string_pretty(&config).unwrap()).unwrap();
+    temp_dir
+}
+
+fn run_hook(project: &TempDir, stdin: &str) -> serde_json::Value {
+    let output = Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .arg("--project")
+        .arg(project.path())
+        .arg("hook")
+        .write_stdin(stdin)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).expect("Hook should print decision JSON")
+}
+
+#[test]
+fn test_remote_approval_allow() {
+    let server = MockNtfyServer::start(Some("Allow"));
+    let project = project_with_approval(&server.url, 10);
+
+    let output = run_hook(&project, PRE_TOOL_USE);
+    assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "allow");
+
+    let published = server.published();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0]["title"], "Approval needed: Bash");
+    assert_eq!(published[0]["actions"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_approval_buttons_never_carry_the_account_token() {
+    let server = MockNtfyServer::start(Some("Allow"));
+    let project = project_with(&server.url, |config| config.ntfy.auth_token = Some("tk_account".to_string()));
+    run_hook(&project, PRE_TOOL_USE);
+    let published = server.published();
+    assert!(!published[0].to_string().contains("tk_account"));
+    assert!(published[0]["actions"][0]["headers"].is_null());
+
+    let server = MockNtfyServer::start(Some("Allow"));
+    let project = project_with(&server.url, |config| {
+        config.ntfy.auth_token = Some("tk_account".to_string());
+        config.approval.reply_token = Some("tk_reply".to_string());
+    });
+    run_hook(&project, PRE_TOOL_USE);
+    let published = server.published();
+    assert!(!published[0].to_string().contains("tk_account"));
+    assert_eq!(published[0]["actions"][0]["headers"]["Authorization"], "Bearer tk_reply");
+}
+
+#[test]
+fn test_remote_approval_deny() {
+    let server = MockNtfyServer::start(Some("Deny"));
+    let project = project_with_approval(&server.url, 10);
+
+    let output = run_hook(&project, PRE_TOOL_USE);
+    assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "deny");
+}
+
+#[test]
+fn test_remote_approval_timeout_falls_back_to_ask() {
+    let server = MockNtfyServer::start(None);
+    let project = project_with_approval(&server.url, 1);
+
+    let output = run_hook(&project, PRE_TOOL_USE);
+    assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "ask");
+}
+
+#[test]
+fn test_partial_approval_section_keeps_defaults() {
+    let mut config = toml::Value::try_from(Config::default()).unwrap();
+    let approval = toml::from_str("backend = \"telegram\"").unwrap();
+    config.as_table_mut().unwrap().insert("approval".to_string(), approval);
+
+    let config: Config = config.try_into().unwrap();
+    assert_eq!(config.approval.backend.as_deref(), Some("telegram"));
+    assert!(!config.approval.enabled);
+    assert_eq!(config.approval.timeout_secs, 120);
+}
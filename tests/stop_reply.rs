@@ -0,0 +1,88 @@
+//! Integration tests for replying to a Stop notification from the phone
+
+mod mock_ntfy;
+
+use assert_cmd::Command;
+use claude_ntfy::Config;
+use mock_ntfy::MockNtfyServer;
+use tempfile::TempDir;
+
+const STOP: &str = r#"{"hook_event_name": "Stop", "session_id": "3f2a-91bc", "stop_hook_active": false}"#;
+
+/// Write a project config pointing at the mock server with Stop replies enabled
+fn project_with_reply(server_url: &str, wait_secs: u64) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.ntfy.server_url = server_url.to_string();
+    config.reply.enabled = true;
+    config.reply.wait_secs = wait_secs;
+
+    let config_dir = temp_dir.path().join(".claude/ntfy-service");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), toml::to_string_pretty(&config).unwrap()).unwrap();
+    temp_dir
+}
+
+fn run_hook(project: &TempDir) -> String {
+    let output = Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .arg("--project")
+        .arg(project.path())
+        .arg("hook")
+        .write_stdin(STOP)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_stop_reply_blocks_with_instruction() {
+    let server = MockNtfyServer::start_replying("Now update the changelog");
+    let project = project_with_reply(&server.url, 10);
+
+    let stdout = run_hook(&project);
+    let output: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(output["decision"], "block");
+    assert_eq!(output["reason"], "Now update the changelog");
+
+    let published = server.published();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0]["actions"][0]["label"], "Reply");
+    assert!(published[0]["actions"][0]["url"].as_str().unwrap().ends_with("-reply-3f2a-91bc"));
+    assert!(published[0]["message"].as_str().unwrap().contains("completed"));
+}
+
+#[test]
+fn test_stop_without_reply_lets_claude_stop() {
+    let server = MockNtfyServer::start(None);
+    let project = project_with_reply(&server.url, 1);
+
+    assert!(run_hook(&project).trim().is_empty());
+    assert_eq!(server.published().len(), 1);
+}
+
+#[test]
+fn test_filtered_stop_neither_notifies_nor_waits() {
+    let server = MockNtfyServer::start_replying("Now update the changelog");
+    let project = project_with_reply(&server.url, 10);
+    let config_path = project.path().join(".claude/ntfy-service/config.toml");
+    let mut config: Config = toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    config.hooks.filters.insert("Stop".to_string(), vec!["!session_id=3f2a-*".to_string()]);
+    std::fs::write(&config_path, toml::to_string_pretty(&config).unwrap()).unwrap();
+
+    assert!(run_hook(&project).trim().is_empty());
+    assert!(server.published().is_empty());
+}
+
+#[test]
+fn test_partial_reply_section_keeps_defaults() {
+    let mut config = toml::Value::try_from(Config::default()).unwrap();
+    let reply = toml::from_str("backend = \"telegram\"").unwrap();
+    config.as_table_mut().unwrap().insert("reply".to_string(), reply);
+
+    let config: Config = config.try_into().unwrap();
+    assert_eq!(config.reply.backend.as_deref(), Some("telegram"));
+    assert!(!config.reply.enabled);
+    assert_eq!(config.reply.wait_secs, 50);
+}
@@ -0,0 +1,108 @@
+//! Integration tests for the Telegram bot backend and reply channel
+
+mod mock_ntfy;
+mod mock_telegram;
+
+use assert_cmd::Command;
+use claude_ntfy::shared::config::{BackendConfig, BackendKind, TelegramBackendConfig};
+use claude_ntfy::Config;
+use mock_ntfy::MockNtfyServer;
+use mock_telegram::{MockTelegramServer, CHAT_ID};
+use tempfile::TempDir;
+
+const POST_TOOL_USE: &str = r#"{"hook_event_name": "PostToolUse", "tool_name": "Edit", "tool_input": {"file_path": "src/main.rs"}}"#;
+const PRE_TOOL_USE: &str = r#"{"hook_event_name": "PreToolUse", "tool_name": "Bash", "tool_input": {"command": "rm -rf build"}}"#;
+const STOP: &str = r#"{"hook_event_name": "Stop", "session_id": "3f2a-91bc", "stop_hook_active": false}"#;
+
+/// Write a project config with a `telegram` backend, letting `configure` adjust it
+fn project_with_telegram(ntfy_url: &str, telegram_url: &str, configure: impl FnOnce(&mut Config)) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.ntfy.server_url = ntfy_url.to_string();
+    config.daemon.enabled = false;
+    config.backends.push(BackendConfig {
+        name: "telegram".to_string(),
+        kind: BackendKind::Telegram(TelegramBackendConfig {
+            bot_token: "123456:test-token".to_string(),
+            chat_id: CHAT_ID.to_string(),
+            api_url: telegram_url.to_string(),
+            timeout_secs: Some(5),
+        }),
+    });
+    configure(&mut config);
+
+    let config_dir = temp_dir.path().join(".claude/ntfy-service");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), toml::to_string_pretty(&config).unwrap()).unwrap();
+    temp_dir
+}
+
+fn run_hook(project: &TempDir, stdin: &str) -> String {
+    // Each test waits for replies from the same bot, which only one process may do at a time
+    let output = Command::cargo_bin("claude-ntfy")
+        .unwrap()
+        .env("HOME", project.path())
+        .arg("--project")
+        .arg(project.path())
+        .arg("hook")
+        .write_stdin(stdin)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_telegram_backend_sends_markdown_v2() {
+    let ntfy = MockNtfyServer::start(None);
+    let telegram = MockTelegramServer::start(None);
+    let project = project_with_telegram(&ntfy.url, &telegram.url, |config| {
+        config.hooks.routes.insert("*".to_string(), vec!["telegram".to_string()]);
+    });
+
+    run_hook(&project, POST_TOOL_USE);
+
+    let sent = telegram.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["chat_id"], CHAT_ID.to_string());
+    assert_eq!(sent[0]["parse_mode"], "MarkdownV2");
+    assert!(sent[0]["text"].as_str().unwrap().contains("*Edit*"));
+    assert!(ntfy.published().is_empty());
+}
+
+#[test]
+fn test_telegram_approval_buttons() {
+    let ntfy = MockNtfyServer::start(None);
+    let telegram = MockTelegramServer::start(Some("Deny"));
+    let project = project_with_telegram(&ntfy.url, &telegram.url, |config| {
+        config.approval.enabled = true;
+        config.approval.timeout_secs = 10;
+        config.approval.backend = Some("telegram".to_string());
+    });
+
+    let output: serde_json::Value = serde_json::from_str(&run_hook(&project, PRE_TOOL_USE)).unwrap();
+    assert_eq!(output["hookSpecificOutput"]["permissionDecision"], "deny");
+
+    let sent = telegram.sent();
+    assert_eq!(sent.len(), 1);
+    let buttons = sent[0]["reply_markup"]["inline_keyboard"][0].as_array().unwrap();
+    assert_eq!(buttons[0]["text"], "Allow");
+    assert!(buttons[1]["callback_data"].as_str().unwrap().starts_with("deny "));
+    assert!(ntfy.published().is_empty());
+}
+
+#[test]
+fn test_telegram_stop_reply() {
+    let ntfy = MockNtfyServer::start(None);
+    let telegram = MockTelegramServer::start_replying("Now update the changelog");
+    let project = project_with_telegram(&ntfy.url, &telegram.url, |config| {
+        config.reply.enabled = true;
+        config.reply.wait_secs = 10;
+        config.reply.backend = Some("telegram".to_string());
+    });
+
+    let output: serde_json::Value = serde_json::from_str(&run_hook(&project, STOP)).unwrap();
+    assert_eq!(output["decision"], "block");
+    assert_eq!(output["reason"], "Now update the changelog");
+    assert!(telegram.sent()[0].get("reply_markup").is_none());
+}